@@ -0,0 +1,218 @@
+//! Throughput and latency of events flowing through running topologies
+//!
+//! Run with `cargo bench`.  Each topology is spawned once per benchmark with
+//! the default config and fed from the benchmark's thread; an event counts as
+//! handled once every output has received its result.
+//!
+//! `throughput` reports events/sec for batches of events sent back to back.
+//! `latency` sends one event at a time and, along with criterion's summary,
+//! prints the p50 & p99 of every round trip it measured.
+//!
+#[macro_use]
+extern crate criterion;
+extern crate cfrp;
+
+use std::cmp;
+use std::default::Default;
+use std::sync::mpsc::*;
+use std::time::{Duration, Instant};
+
+use criterion::{Criterion, Throughput};
+use cfrp::*;
+
+/// Events sent per iteration of the throughput benchmarks
+const BATCH: usize = 1000;
+
+/// Width of the `lift2` tree and the number of fan-out branches
+const WIDTH: usize = 8;
+
+/// Number of inputs which never receive data
+const IDLE: usize = 40;
+
+// A running topology with one input being benchmarked
+struct Bench {
+    window: usize,
+    tx: Sender<usize>,
+    outputs: Vec<Receiver<usize>>,
+    _idle: Vec<Sender<usize>>,
+    _handle: TopologyHandle,
+}
+
+impl Bench {
+    // Spawns the topology built by `f` from the benchmarked input.  Senders
+    // for any other inputs are kept in `idle`, as the topology stops once an
+    // input is disconnected.  At most `window` events are sent before their
+    // results are waited for
+    fn new<F>(window: usize, f: F) -> Bench where
+        F: FnOnce(&Builder, Receiver<usize>, &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>>,
+    {
+        let (tx, rx) = channel();
+        let mut idle = Vec::new();
+        let mut outputs = Vec::new();
+
+        let mut handle = spawn_topology(Default::default(), |t| {
+            outputs = f(t, rx, &mut idle);
+        });
+        handle.set_drop_policy(DropPolicy::Stop);
+
+        // Initial values
+        for output in outputs.iter() {
+            output.recv().unwrap();
+        }
+
+        Bench { window, tx, outputs, _idle: idle, _handle: handle }
+    }
+
+    fn events(&self, n: usize) {
+        let mut sent = 0;
+        while sent < n {
+            let window = cmp::min(self.window, n - sent);
+            for i in 0..window {
+                self.tx.send(sent + i).unwrap();
+            }
+
+            for output in self.outputs.iter() {
+                for _ in 0..window {
+                    output.recv().unwrap();
+                }
+            }
+            sent += window;
+        }
+    }
+}
+
+fn chain(t: &Builder, rx: Receiver<usize>, _: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    vec![t.output(t.listen(0, rx).lift(|i| { i + 1 }).fold(0, |sum, i| { sum + i }))]
+}
+
+fn lift2_tree(t: &Builder, rx: Receiver<usize>, _: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    let input = t.listen(0, rx);
+
+    let mut level = (0..WIDTH).map(|i| {
+        Box::new(input.clone().lift(move |j| { i + j })) as Box<Signal<usize>>
+    }).collect::<Vec<_>>();
+
+    while level.len() > 1 {
+        let mut next = Vec::new();
+        while level.len() > 1 {
+            let right = level.pop().unwrap();
+            let left = level.pop().unwrap();
+            next.push(Box::new(left.lift2(right, |l, r| { *l + *r })) as Box<Signal<usize>>);
+        }
+        next.extend(level);
+        level = next;
+    }
+
+    vec![t.output(level.pop().unwrap())]
+}
+
+fn fan_out(t: &Builder, rx: Receiver<usize>, _: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    let input = t.listen(0, rx);
+
+    (0..WIDTH).map(|i| {
+        t.output(input.clone().lift(move |j| { i + j }))
+    }).collect()
+}
+
+fn async(t: &Builder, rx: Receiver<usize>, _: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    let result = t.listen(0, rx).lift(|i| { i + 1 }).async(t);
+
+    vec![t.output(result.lift(|i| { i * 2 }))]
+}
+
+// Idle inputs with subgraphs of their own, which the benchmarked input never
+// needs to send `Unchanged` to
+fn idle_inputs(t: &Builder, rx: Receiver<usize>, idle: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    for _ in 0..IDLE {
+        let (tx, rx) = channel();
+        idle.push(tx);
+        t.add(t.listen(0usize, rx).lift(|i| { i + 1 }));
+    }
+
+    chain(t, rx, idle)
+}
+
+// Idle inputs combined with the benchmarked input, which are sent `Unchanged`
+// for every event
+fn idle_inputs_joined(t: &Builder, rx: Receiver<usize>, idle: &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>> {
+    let mut signals = vec![Box::new(t.listen(0, rx)) as Box<Signal<usize>>];
+    for _ in 0..IDLE {
+        let (tx, rx) = channel();
+        idle.push(tx);
+        signals.push(Box::new(t.listen(0, rx)));
+    }
+
+    vec![t.output(t.lift_all(signals, |values| {
+        values.into_iter().map(|v| v.into_inner()).sum::<usize>()
+    }))]
+}
+
+type Setup = fn(&Builder, Receiver<usize>, &mut Vec<Sender<usize>>) -> Vec<Receiver<usize>>;
+
+// Each topology, with the number of events which can be sent to it at once
+//
+// Async results re-enter the topology through an input, which needs the
+// dispatch lock; an input holding the lock while the async subgraph waits for
+// its last result to be taken deadlocks, so events are sent one at a time.
+const SETUPS: [(&str, usize, Setup); 6] = [
+    ("listen_lift_fold", BATCH, chain),
+    ("lift2_tree", BATCH, lift2_tree),
+    ("fan_out", BATCH, fan_out),
+    ("async", 1, async),
+    ("idle_inputs", BATCH, idle_inputs),
+    ("idle_inputs_joined", BATCH, idle_inputs_joined),
+];
+
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    for &(name, window, setup) in SETUPS.iter() {
+        let bench = Bench::new(window, setup);
+        group.bench_function(name, |b| b.iter(|| bench.events(BATCH)));
+    }
+
+    group.finish();
+}
+
+fn latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("latency");
+    let mut percentiles = Vec::new();
+
+    for &(name, window, setup) in SETUPS.iter() {
+        let bench = Bench::new(window, setup);
+        let mut samples = Vec::new();
+
+        group.bench_function(name, |b| b.iter_custom(|iters| {
+            let mut total = Duration::from_secs(0);
+            for _ in 0..iters {
+                let start = Instant::now();
+                bench.events(1);
+                let elapsed = start.elapsed();
+
+                samples.push(elapsed);
+                total += elapsed;
+            }
+            total
+        }));
+
+        samples.sort();
+        percentiles.push((name, percentile(&samples, 50), percentile(&samples, 99)));
+    }
+
+    group.finish();
+
+    for (name, p50, p99) in percentiles.into_iter() {
+        println!("latency/{}: p50 {:?}, p99 {:?}", name, p50, p99);
+    }
+}
+
+// The `p`th percentile of `samples`, which must be sorted
+fn percentile(samples: &[Duration], p: usize) -> Duration {
+    if samples.is_empty() { return Duration::from_secs(0) }
+
+    samples[(samples.len() - 1) * p / 100]
+}
+
+criterion_group!(benches, throughput, latency);
+criterion_main!(benches);
@@ -1,5 +1,3 @@
-use std::thread;
-use std::iter;
 use std::cell::*;
 use std::sync::*;
 use std::sync::mpsc::*;
@@ -9,12 +7,18 @@ use std::time::{Duration, Instant};
 
 use rand;
 
-use super::{Signal, SignalExt, Run, Config};
-use primitives::input::{RunInput, ReceiverInput, AckInput, RngInput};
-use primitives::fork::{Fork, Branch};
+use super::{Signal, SignalExt, Run, Config, Topology, Node};
+use executor::Executor;
+use primitives::input::{RunInput, ReceiverInput, TimerInput, FpsInput, TimedInput, Timing, AckInput, RngInput};
+use primitives::fork::{Fork, Branch, Sinks};
 use primitives::channel::Channel;
-use primitives::async::Async;
+use primitives::async::{Async, AsyncSender};
+use primitives::link::link;
+use primitives::merge::MergeSignal;
+use primitives::lift_all::LiftAllSignal;
+use primitives::sink::Sink;
 use primitives::value::Value;
+use graph;
 
 /// `Builder` provides helpers for building topologies
 ///
@@ -28,6 +32,11 @@ impl Builder {
     /// Create a new Builder
     ///
     pub fn new(config: Config) -> Self {
+        let mut config = config;
+        config.start_runtime();
+        config.set_name(None);
+        config.set_defaults();
+
         Builder {
             config: config,
             runners: RefCell::new(Vec::new()),
@@ -35,11 +44,72 @@ impl Builder {
         }
     }
 
+    /// Create a builder for nodes joining a topology built with `config`
+    ///
+    /// Unlike `new`, the builder's nodes share the topology's supervisor,
+    /// scheduler and metrics.  Used by `TopologyHandle::attach`.
+    ///
+    pub(crate) fn sharing(config: Config) -> Self {
+        Builder {
+            config,
+            runners: RefCell::new(Vec::new()),
+            inputs: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Returns a copy of the builder's Config
+    ///
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    /// Create a topology from the inputs & transformations which have been
+    /// added to the builder
+    ///
+    pub fn build(self) -> Topology {
+        Topology::with_config(self.config, self.inputs.into_inner(), self.runners.into_inner())
+    }
+
+    /// Render the topology built so far as a Graphviz DOT graph
+    ///
+    /// Only signals which have been added to the builder are included.
+    /// Inputs are drawn as boxes, forks (the results of `add`) as diamonds,
+    /// and `async` boundaries as double boxes with dashed edges leaving them.
+    /// Constant signals are drawn in grey.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut dot = String::new();
+    ///
+    /// spawn_topology(Default::default(), |t| {
+    ///     let input = t.listen(0, rx);
+    ///     t.add(input.lift2(t.value(1), |i, j| { *i + *j }));
+    ///
+    ///     dot = t.to_dot();
+    /// });
+    ///
+    /// assert!(dot.starts_with("digraph topology {"));
+    /// assert!(dot.contains("[label=\"lift2\"]"));
+    /// assert!(dot.contains("[label=\"value\", color=grey, fontcolor=grey]"));
+    /// ```
+    ///
+    pub fn to_dot(&self) -> String {
+        let roots = self.runners.borrow().iter().map(|r| r.describe()).collect::<Vec<Node>>();
+        graph::to_dot(&roots)
+    }
+
     /// Listen to `input` and push received data into the topology
     ///
     /// All data must enter the topology via a call to `listen`; this function
     /// ensures data syncronization across the topology.  Each listener runs in 
-    /// its own thread
+    /// its own thread when using `Executor::ThreadPerNode`, otherwise the
+    /// topology's workers take turns reading from them
     ///
     /// # Example
     ///
@@ -55,20 +125,21 @@ impl Builder {
     /// // Receive data on `rx` and expose it as a signal with initial value 
     /// //`initial`.  This is necessary because the topology must maintain 
     /// // consistency between threads, so any message sent to any input is 
-    /// // propagated to all other inputs as "no-change" messages.
+    /// // propagated as "no-change" messages to the inputs it's combined with.
     /// let signal = b.listen(0, rx);
     /// ```
     ///
     pub fn listen<A>(&self, initial: A, input: Receiver<A>) -> Branch<A> where
         A: 'static + Clone + Send,
     {
-        let (tx, rx) = sync_channel(self.config.buffer_size.clone());
+        let (tx, rx) = link(&self.config, "listen");
 
-        let runner = ReceiverInput::new(input, tx);
+        let node = Node::shared("listen", None, false, vec![]);
+        let runner = ReceiverInput::new(input, tx, node.clone());
 
         self.inputs.borrow_mut().push(Box::new(runner));
 
-        self.add(Channel::new(self.config.clone(), rx, initial))
+        self.add(Channel::new(self.config.clone(), rx, initial, node))
     }
 
     /// Creats a channel with constant value `v`
@@ -110,35 +181,169 @@ impl Builder {
     /// writing to the topology blocks or the scheduling thread is pre-empted
     /// by other threads, however a signal with every interval's time value will
     /// eventually be sent.
-    /// 
+    ///
+    /// Time is read from the `clock` of the builder's `Config`, so a
+    /// `VirtualClock` can be used to control it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let mut out = None;
+    ///
+    /// spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(t.every(Duration::from_millis(10))));
+    /// });
+    ///
+    /// let out_rx = out.unwrap();
+    /// let start = out_rx.recv().unwrap();
+    /// assert_eq!(out_rx.recv().unwrap() - start, Duration::from_millis(10));
+    /// assert_eq!(out_rx.recv().unwrap() - start, Duration::from_millis(20));
+    /// ```
+    ///
     pub fn every(&self, interval: Duration) -> Branch<Instant>
     {
-        let (tx, rx) = sync_channel(0);
-        let initial = Instant::now();
-
-        let mut last_tick = initial.clone();
-        thread::spawn(move || {
-            loop {
-                let tm = iter::repeat(interval)
-                    .scan(last_tick.clone(), |tm, d| {
-                        *tm = *tm + d;
-                        Some(*tm)
-                    })
-                    .inspect(|tm| { 
-                        match tx.send(tm.clone()) {
-                            Ok(_) => { last_tick = *tm; },
-                            Err(_) => return,
-                        }
-                    })
-                    .take_while(|tm| { *tm < Instant::now() })
-                    .last();
-
-                let sleep_duration = Instant::now() - tm.unwrap();
-                thread::sleep(sleep_duration);
-            }
-        });
+        let (tx, rx) = link(&self.config, "every");
+        let initial = self.config.clock.now();
+
+        let node = Node::shared("every", None, false, vec![]);
+        let runner = TimerInput::new(self.config.clock.clone(), interval, initial, tx, node.clone());
+
+        self.inputs.borrow_mut().push(Box::new(runner));
+
+        self.add(Channel::new(self.config.clone(), rx, initial, node))
+    }
+
+    /// Returns a signal which emits the time since its last frame, aiming for
+    /// `target` frames per second
+    ///
+    /// Unlike `every`, missed frames aren't caught up on.  If a frame is
+    /// late, because writing to the topology blocked or the clock jumped, the
+    /// frames which should have happened in the meantime are skipped; the
+    /// late frame's duration covers all of the time since the last one, and
+    /// frames carry on `1 / target` seconds after it.
+    ///
+    /// The initial value is a duration of zero.  Time is read from the
+    /// `clock` of the builder's `Config`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` is 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let mut out = None;
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     out = Some(t.output(t.fps(10)));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), Duration::from_secs(0));
+    ///
+    /// clock.advance(Duration::from_millis(100));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_recv().unwrap(), Duration::from_millis(100));
+    ///
+    /// // A stall produces one long frame rather than a burst of short ones
+    /// clock.advance(Duration::from_secs(1));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<Duration>>(), vec![Duration::from_secs(1)]);
+    /// ```
+    ///
+    pub fn fps(&self, target: u32) -> Branch<Duration>
+    {
+        let node = Node::shared("fps", None, false, vec![]);
+        self.frames(target, None, true, node)
+    }
+
+    /// Returns a signal which emits the time since its last frame, aiming for
+    /// `target` frames per second while `gate` is true
+    ///
+    /// Frames are paused while `gate` is false, and time spent paused isn't
+    /// counted: the first frame after `gate` becomes true again covers the
+    /// time since then.  Changes to `gate` are handled like `async` results,
+    /// as their own events.  Missed frames are skipped, see `fps`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` is 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     let playing = t.listen(false, rx);
+    ///     out = Some(t.output(t.fps_when(10, playing)));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), Duration::from_secs(0));
+    ///
+    /// // Paused
+    /// clock.advance(Duration::from_secs(1));
+    /// while t.step() {}
+    /// assert!(out_rx.try_recv().is_err());
+    ///
+    /// t.push(0, true);
+    /// while t.step() {}
+    /// clock.advance(Duration::from_millis(100));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_recv().unwrap(), Duration::from_millis(100));
+    /// ```
+    ///
+    pub fn fps_when<SB>(&self, target: u32, gate: SB) -> Branch<Duration> where
+        SB: 'static + Signal<bool>,
+    {
+        // `gate` may be a branch, which only receives from its fork once
+        // it's been initialized
+        let mut gate = gate;
+        gate.init();
+        let running = gate.initial().unwrap();
 
-        self.listen(initial, rx)
+        // Gate changes are sent to the input as they happen, the same way as
+        // `async` results
+        let (tx, rx) = channel();
+        let node = Node::shared("fps", None, false, vec![gate.describe()]);
+
+        let pusher = Async::new(Box::new(gate), AsyncSender::Unbounded(tx), node.clone());
+        self.runners.borrow_mut().push(Box::new(pusher));
+
+        self.frames(target, Some(rx), running, node)
+    }
+
+    // Adds an `FpsInput` for `fps` and `fps_when`
+    fn frames(&self, target: u32, gate: Option<Receiver<bool>>, running: bool, node: Node) -> Branch<Duration>
+    {
+        let interval = Duration::from_secs(1) / target;
+        let (tx, rx) = link(&self.config, "fps");
+
+        let runner = FpsInput::new(self.config.clock.clone(), interval, self.config.clock.now(), gate, running, tx, node.clone());
+        self.inputs.borrow_mut().push(Box::new(runner));
+
+        self.add(Channel::new(self.config.clone(), rx, Duration::from_secs(0), node))
     }
 
     /// Creates a channel which pushes `Event::Changed(initial)` when any 
@@ -171,13 +376,14 @@ impl Builder {
     pub fn ack_value<A>(&self, initial: A) -> Branch<A> where
         A: 'static + Clone + Send,
     {
-        let (tx, rx) = sync_channel(self.config.buffer_size.clone());
+        let (tx, rx) = link(&self.config, "ack_value");
 
-        let runner = AckInput::new(initial.clone(), tx);
+        let node = Node::shared("ack_value", None, false, vec![]);
+        let runner = AckInput::new(initial.clone(), tx, node.clone());
 
         self.inputs.borrow_mut().push(Box::new(runner));
 
-        self.add(Channel::new(self.config.clone(), rx, initial))
+        self.add(Channel::new(self.config.clone(), rx, initial, node))
     }
 
     /// Return a signal that increments each time the topology receives data
@@ -226,14 +432,115 @@ impl Builder {
     R: 'static + rand::Rng + Clone + Send,
     A: 'static + Send + Clone + rand::Rand,
     {
-        let (tx, rx) = sync_channel(self.config.buffer_size.clone());
+        let (tx, rx) = link(&self.config, "ack_random");
 
         let initial = rng.gen();
-        let runner = RngInput::new(rng, tx);
+        let node = Node::shared("ack_random", None, false, vec![]);
+        let runner = RngInput::new(rng, tx, node.clone());
 
         self.inputs.borrow_mut().push(Box::new(runner));
 
-        self.add(Channel::new(self.config.clone(), rx, initial))
+        self.add(Channel::new(self.config.clone(), rx, initial, node))
+    }
+
+    /// Combine any number of signals of the same type into a signal which
+    /// emits whichever value changed
+    ///
+    /// This is the n-ary version of `SignalExt::merge`; the initial value is
+    /// taken from the first signal, and if several signals change as part of
+    /// the same event the earliest one in `signals` wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `signals` is empty
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     let a = t.listen(0, rx);
+    ///
+    ///     t.merge_all(vec![
+    ///         Box::new(a.clone().lift(|i| { i * 10 })) as Box<Signal<usize>>,
+    ///         Box::new(a.lift(|i| { i + 1 })),
+    ///         Box::new(t.value(7)),
+    ///     ])
+    ///     .lift(move |i| { out_tx.send(i).unwrap(); })
+    ///     .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// // Both sides changed, the first one wins
+    /// tx.send(2).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 20);
+    /// ```
+    ///
+    pub fn merge_all<A>(&self, mut signals: Vec<Box<Signal<A>>>) -> MergeSignal<A> where
+        A: 'static + Clone + Send,
+    {
+        for signal in signals.iter_mut() {
+            signal.init();
+        }
+
+        MergeSignal::new(self.config.clone(), signals)
+    }
+
+    /// Combine any number of signals of the same type into an output signal
+    ///
+    /// `f` is passed a `Value<A>` for each signal, in the same order as
+    /// `signals`.  Like `lift2`, it's only called when at least one of the
+    /// signals has changed.  All the signals are received from in a single
+    /// loop, so wide joins don't need to be built from nested `lift2` calls.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     let signals = vec![
+    ///         Box::new(t.listen(1usize, rx)) as Box<Signal<usize>>,
+    ///         Box::new(t.value(2)),
+    ///         Box::new(t.value(3)),
+    ///     ];
+    ///
+    ///     t.lift_all(signals, move |values| {
+    ///         out_tx.send(values.into_iter().map(|v| v.into_inner()).sum::<usize>()).unwrap();
+    ///     })
+    ///     .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 6);
+    ///
+    /// tx.send(4).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 9);
+    /// ```
+    ///
+    pub fn lift_all<F, A, B>(&self, mut signals: Vec<Box<Signal<A>>>, f: F) -> LiftAllSignal<F, A, B> where
+        F: 'static + Send + Fn(Vec<::value::Value<A>>) -> B,
+        A: 'static + Clone + Send,
+        B: 'static + Clone + Send,
+    {
+        for signal in signals.iter_mut() {
+            signal.init();
+        }
+
+        LiftAllSignal::new(self.config.clone(), signals, f)
     }
 
     /// Add a signal to the topology
@@ -269,14 +576,103 @@ impl Builder {
         A: 'static + Clone + Send,
     {
         let v = root.initial();
+        let node = Node::shared("fork", None, v.is_constant(), vec![root.describe()]);
+
+        let fork_txs = Arc::new(Mutex::new(Sinks::new()));
 
-        let fork_txs = Arc::new(Mutex::new(Vec::new()));
+        // Branches carry the name of the signal they were forked from
+        let mut config = self.config.clone();
+        config.set_name(root.config().name());
 
-        let fork = Fork::new(Box::new(root), fork_txs.clone());
+        let fused = Arc::new(Mutex::new(None));
+        let fork = Fork::new(Box::new(root), fork_txs.clone(), fused.clone(), node.clone());
 
         self.runners.borrow_mut().push(Box::new(fork));
 
-        Branch::new(self.config.clone(), fork_txs, None, v)
+        Branch::new(config, fork_txs, fused, None, v, node)
+    }
+
+    /// Add a signal to the topology, passing each of its values to `f`
+    ///
+    /// `f` is called with the signal's initial value when the topology starts
+    /// and with every changed value after that; unchanged events are not
+    /// passed along.  `f` is dropped once the topology exits.
+    /// `SignalExt<A>` also provides `sink(&Builder, F)` for method-chaining
+    /// syntax.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.sink(t.listen(0, rx), move |i| { out_tx.send(i).unwrap(); });
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 1);
+    /// ```
+    ///
+    pub fn sink<SA, A, F>(&self, root: SA, f: F) where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+        F: 'static + Send + Fn(A),
+    {
+        let mut root = root;
+        root.init();
+
+        let sink = Sink::new(Box::new(root), f);
+        self.runners.borrow_mut().push(Box::new(sink));
+    }
+
+    /// Add a signal to the topology and return a channel receiving its values
+    ///
+    /// The receiver gets the signal's initial value when the topology starts
+    /// and every changed value after that.  It's closed once the topology
+    /// exits, so iterating over it will terminate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(0, rx).lift(|i| { i + 1 })));
+    /// });
+    ///
+    /// let out_rx = out.unwrap();
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// drop(tx);
+    ///
+    /// assert_eq!(out_rx.iter().collect::<Vec<usize>>(), vec![1, 2, 3]);
+    /// ```
+    ///
+    pub fn output<SA, A>(&self, root: SA) -> Receiver<A> where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+    {
+        let (tx, rx) = channel();
+
+        self.sink(root, move |a| {
+            // Nothing to do if the receiver has been dropped
+            let _ = tx.send(a);
+        });
+
+        rx
     }
 
     /// Combination of adding a signal and a channel
@@ -284,7 +680,10 @@ impl Builder {
     /// Async allows signals to be processed downstream out of order.  Internally,
     /// the output of `root` is sent to new input channel.  The result is that
     /// long-running processes can be handled outside of the synchronized topology
-    /// process, and the result can be handled when it's available.
+    /// process, and the result can be handled when it's available.  With
+    /// `Executor::Cooperative` there's only one thread to run them on, so the
+    /// result still arrives out of order but the rest of the topology waits
+    /// for it to be computed.
     ///
     /// ```
     /// use std::thread;
@@ -328,11 +727,192 @@ impl Builder {
         A: 'static + Clone + Send,
     {
         let v = root.initial();
-        let (tx, rx) = sync_channel(self.config.buffer_size.clone());
-        let pusher = Async::new(Box::new(root), tx);
+
+        let (tx, rx) = match self.config.executor {
+            Executor::ThreadPerNode => {
+                let (tx, rx) = sync_channel(self.config.buffer_size);
+                (AsyncSender::Bounded(tx), rx)
+            },
+            _ => {
+                // Workers can't wait for the value to be received, as they
+                // might be the ones which need to receive it
+                let (tx, rx) = channel();
+                (AsyncSender::Unbounded(tx), rx)
+            },
+        };
+        // The async subgraph and the input receiving its values are drawn as
+        // one node
+        let node = Node::shared("async", None, v.is_constant(), vec![root.describe()]);
+
+        let pusher = Async::new(Box::new(root), tx, node.clone());
         self.runners.borrow_mut().push(Box::new(pusher));
 
-        self.listen(v.unwrap(), rx)
+        let (input_tx, input_rx) = link(&self.config, "async");
+        self.inputs.borrow_mut().push(Box::new(ReceiverInput::new(rx, input_tx, node.clone())));
+
+        self.add(Channel::new(self.config.clone(), input_rx, v.unwrap(), node))
+    }
+
+    /// Emit `root`'s value once `wait` has passed without it changing
+    ///
+    /// Like `async`, values are emitted as their own events, so nodes
+    /// downstream of the result see them after any events which arrived in
+    /// the meantime.  Time is read from the `clock` of the builder's
+    /// `Config`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx).debounce(t, Duration::from_secs(1))));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 0);
+    ///
+    /// t.push(0, 1usize);
+    /// clock.advance(Duration::from_millis(500));
+    /// t.push(0, 2usize);
+    /// clock.advance(Duration::from_millis(500));
+    /// while t.step() {}
+    ///
+    /// // 1 was replaced before it was due
+    /// assert!(out_rx.try_recv().is_err());
+    ///
+    /// clock.advance(Duration::from_millis(500));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_recv().unwrap(), 2);
+    /// ```
+    ///
+    pub fn debounce<SA, A>(&self, root: SA, wait: Duration) -> Branch<A> where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+    {
+        self.timed(root, "debounce", Timing::Debounce(wait))
     }
 
+    /// Emit `root`'s value at most once every `interval`
+    ///
+    /// The first change is emitted straight away; changes during the
+    /// following `interval` are held, and the latest of them is emitted when
+    /// it ends.  Values are emitted as their own events, see `debounce`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx).throttle(t, Duration::from_secs(1))));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 0);
+    ///
+    /// t.push(0, 1usize);
+    /// t.push(0, 2usize);
+    /// t.push(0, 3usize);
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<usize>>(), vec![1]);
+    ///
+    /// clock.advance(Duration::from_secs(1));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<usize>>(), vec![3]);
+    /// ```
+    ///
+    pub fn throttle<SA, A>(&self, root: SA, interval: Duration) -> Branch<A> where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+    {
+        self.timed(root, "throttle", Timing::Throttle(interval))
+    }
+
+    /// Emit each of `root`'s values once `by` has passed
+    ///
+    /// Every value is emitted, in order, as its own event, see `debounce`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx).delay(t, Duration::from_secs(1))));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 0);
+    ///
+    /// t.push(0, 1usize);
+    /// t.push(0, 2usize);
+    /// while t.step() {}
+    /// assert!(out_rx.try_recv().is_err());
+    ///
+    /// clock.advance(Duration::from_secs(1));
+    /// while t.step() {}
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<usize>>(), vec![1, 2]);
+    /// ```
+    ///
+    pub fn delay<SA, A>(&self, root: SA, by: Duration) -> Branch<A> where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+    {
+        self.timed(root, "delay", Timing::Delay(by))
+    }
+
+    // Sends `root`'s values to a `TimedInput`, which re-enters them into the
+    // topology according to `timing`
+    fn timed<SA, A>(&self, root: SA, kind: &'static str, timing: Timing) -> Branch<A> where
+        SA: 'static + Signal<A>,
+        A: 'static + Clone + Send,
+    {
+        // `root` may be a branch, which only receives from its fork once
+        // it's been initialized
+        let mut root = root;
+        root.init();
+        let v = root.initial();
+
+        // Values are held by the input until they're due anyway, so there's
+        // nothing to gain from blocking the sender
+        let (tx, rx) = channel();
+        let node = Node::shared(kind, None, v.is_constant(), vec![root.describe()]);
+
+        let pusher = Async::new(Box::new(root), AsyncSender::Timed(tx, self.config.clock.clone()), node.clone());
+        self.runners.borrow_mut().push(Box::new(pusher));
+
+        let (input_tx, input_rx) = link(&self.config, kind);
+        let input = TimedInput::new(self.config.clock.clone(), timing, rx, input_tx, node.clone());
+        self.inputs.borrow_mut().push(Box::new(input));
+
+        self.add(Channel::new(self.config.clone(), input_rx, v.unwrap(), node))
+    }
 }
@@ -0,0 +1,115 @@
+use std::cmp;
+use std::fmt;
+use std::sync::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A source of time for time-based signals such as `Builder::every`
+///
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// The current time
+    fn now(&self) -> Instant;
+
+    /// Block until `deadline` has passed or `timeout` has elapsed in real
+    /// time, whichever is first.  Returns true if `deadline` has passed
+    fn wait_until(&self, deadline: Instant, timeout: Duration) -> bool;
+}
+
+/// The system's clock
+///
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wait_until(&self, deadline: Instant, timeout: Duration) -> bool {
+        let now = Instant::now();
+        if now >= deadline { return true }
+
+        thread::sleep(cmp::min(deadline - now, timeout));
+        Instant::now() >= deadline
+    }
+}
+
+/// A clock which only moves when it's told to
+///
+/// Clones of a `VirtualClock` share the same time, so a clone can be used in
+/// a topology's `Config` while the original is advanced by a test.
+///
+/// # Example
+///
+/// ```
+/// use std::default::Default;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use cfrp::*;
+///
+/// let clock = VirtualClock::new();
+/// let mut config: Config = Default::default();
+/// config.clock = Arc::new(clock.clone());
+///
+/// let mut out = None;
+/// let mut t = TestTopology::new(config, |t| {
+///     out = Some(t.output(t.every(Duration::from_secs(1)).fold(0, |n, _| { n + 1 })));
+/// });
+/// let out_rx = out.unwrap();
+///
+/// // The initial value counts as a tick
+/// assert_eq!(out_rx.try_recv().unwrap(), 1);
+///
+/// // Ten minutes pass instantly
+/// clock.advance(Duration::from_secs(600));
+/// while t.step() {}
+///
+/// assert_eq!(out_rx.try_iter().last(), Some(601));
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    inner: Arc<(Mutex<Instant>, Condvar)>,
+}
+
+impl VirtualClock {
+    /// Create a new clock, starting at the current time
+    ///
+    pub fn new() -> VirtualClock {
+        VirtualClock {
+            inner: Arc::new((Mutex::new(Instant::now()), Condvar::new())),
+        }
+    }
+
+    /// Move the clock forward by `by`
+    ///
+    pub fn advance(&self, by: Duration) {
+        let (now, changed) = &*self.inner;
+
+        let mut now = now.lock().unwrap();
+        *now += by;
+        changed.notify_all();
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> VirtualClock {
+        VirtualClock::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        *self.inner.0.lock().unwrap()
+    }
+
+    fn wait_until(&self, deadline: Instant, timeout: Duration) -> bool {
+        let (now, changed) = &*self.inner;
+
+        let now = now.lock().unwrap();
+        if *now >= deadline { return true }
+
+        let (now, _) = changed.wait_timeout(now, timeout).unwrap();
+        *now >= deadline
+    }
+}
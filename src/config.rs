@@ -1,20 +1,243 @@
 use std::default::*;
+use std::sync::Arc;
+
+use clock::{Clock, SystemClock};
+use executor::{Executor, Scheduler};
+use metrics::Metrics;
+use supervisor::Supervisor;
 
 /// Topology Configuration
-/// 
-/// `buffer_size` determines the size of the SyncSender channels to use for
-/// transporting events between threads.  Smaller values _may_ result in less 
+///
+/// `buffer_size` determines the size of the queues used for transporting
+/// events between nodes.  Smaller values _may_ result in less
 /// memory consumption, larger values _may_ result in higher throughput.
 ///
+/// `backpressure` determines what happens when data is sent to a node faster
+/// than it can handle it.
+///
+/// `panic_policy` determines what happens when a function passed to a node
+/// (for instance to `lift` or `fold`) panics.
+///
+/// `clock` is the source of time for time-based signals such as
+/// `Builder::every`.
+///
+/// `executor` determines how the topology's nodes are mapped onto threads.
+///
+/// Nodes can be named with `SignalExt::named`, which is used for their
+/// threads, log lines, errors and metrics.
+///
+/// The config passed to a `Builder` is used for every node it builds.  The
+/// settings in `Overrides` can be changed for the link feeding a single
+/// consumer of a `Branch` with `Branch::with_config`.
+///
 #[derive(Clone, Debug)]
 pub struct Config {
     pub buffer_size: usize,
+    pub backpressure: BackpressurePolicy,
+    pub panic_policy: PanicPolicy,
+    pub clock: Arc<Clock>,
+    pub executor: Executor,
+    // Set for a single node by `SignalExt::named`
+    name: Option<String>,
+    // Shared by the nodes of a topology, `Builder::new` gives each topology
+    // its own
+    runtime: Runtime,
+    // The config nodes are built with, if this belongs to a `Builder`
+    defaults: Option<Arc<Config>>,
+}
+
+// What a running topology's nodes share: `supervisor` collects errors from
+// them, `scheduler` hands out work to the topology's workers and `metrics`
+// collects their counters
+#[derive(Clone, Debug)]
+struct Runtime {
+    supervisor: Supervisor,
+    scheduler: Scheduler,
+    metrics: Metrics,
+}
+
+impl Runtime {
+    fn new() -> Runtime {
+        Runtime {
+            supervisor: Supervisor::new(),
+            scheduler: Scheduler::new(),
+            metrics: Metrics::new(),
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Config {
         Config {
             buffer_size: 0,
+            backpressure: BackpressurePolicy::Block,
+            panic_policy: PanicPolicy::Shutdown,
+            clock: Arc::new(SystemClock),
+            executor: Executor::ThreadPerNode,
+            name: None,
+            runtime: Runtime::new(),
+            defaults: None,
+        }
+    }
+}
+
+impl Config {
+    /// The name of the node, if it's been named
+    ///
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// The name of the node, or `kind` if it hasn't been named
+    ///
+    pub fn node_name<'a>(&'a self, kind: &'a str) -> &'a str {
+        match self.name {
+            Some(ref name) => name,
+            None => kind,
+        }
+    }
+
+    pub(crate) fn supervisor(&self) -> &Supervisor {
+        &self.runtime.supervisor
+    }
+
+    pub(crate) fn scheduler(&self) -> &Scheduler {
+        &self.runtime.scheduler
+    }
+
+    pub(crate) fn metrics(&self) -> &Metrics {
+        &self.runtime.metrics
+    }
+
+    // Gives the config a supervisor, scheduler and metrics of its own, for
+    // the nodes of a new topology
+    pub(crate) fn start_runtime(&mut self) {
+        self.runtime = Runtime::new();
+    }
+
+    // Uses this config as the defaults for nodes built from it
+    pub(crate) fn set_defaults(&mut self) {
+        self.defaults = None;
+        self.defaults = Some(Arc::new(self.clone()));
+    }
+
+    // The config for a node built from a node with this config.  Overrides
+    // made for a single node, such as its name, aren't passed on; the new
+    // node starts out with the defaults set with `set_defaults`
+    pub(crate) fn child(&self) -> Config {
+        match self.defaults {
+            Some(ref defaults) => {
+                let mut config = (**defaults).clone();
+                config.defaults = Some(defaults.clone());
+                config
+            },
+            None => {
+                let mut config = self.clone();
+                config.name = None;
+                config
+            },
+        }
+    }
+
+    pub(crate) fn overrides(&self) -> Overrides {
+        Overrides {
+            buffer_size: self.buffer_size,
+            backpressure: self.backpressure,
         }
     }
+
+    pub(crate) fn set_overrides(&mut self, overrides: Overrides) {
+        self.buffer_size = overrides.buffer_size;
+        self.backpressure = overrides.backpressure;
+    }
+}
+
+/// The settings which can be changed for a single node
+///
+/// Both apply to the link feeding the node, and start out as the builder's
+/// `buffer_size` and `backpressure`.  Settings shared by the whole topology,
+/// such as its `executor`, `clock` and `panic_policy`, can't be overridden.
+/// Threads take their names from `SignalExt::named`; thread priority isn't
+/// configurable, as there's no portable way to set it.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Overrides {
+    pub buffer_size: usize,
+    pub backpressure: BackpressurePolicy,
+}
+
+/// What to do when a node is sent events faster than it can handle them
+///
+/// Policies apply to the links between nodes, which hold up to `buffer_size`
+/// waiting values (or one, for the lossy policies with a `buffer_size` of 0).
+///
+/// Lossy policies drop values rather than events: a dropped value is
+/// replaced by `Event::Unchanged`, so nodes downstream still see every event
+/// and joins (for instance with `lift2`) stay in step, pairing the values
+/// they do see with the events they came from.  Dropped values are counted
+/// in `NodeStats::dropped`, and `Event::Exit` is never dropped.
+///
+/// # Example
+///
+/// ```
+/// use std::default::Default;
+/// use std::sync::mpsc::*;
+/// use std::thread;
+/// use std::time::Duration;
+/// use cfrp::*;
+///
+/// let mut config: Config = Default::default();
+/// config.backpressure = BackpressurePolicy::Latest;
+///
+/// let (in_tx, in_rx) = channel();
+/// let mut out = None;
+///
+/// spawn_topology(config, |t| {
+///     // A slow consumer no longer holds up the input, it skips to the
+///     // latest value instead
+///     out = Some(t.output(t.listen(0, in_rx).lift(|i| { thread::sleep(Duration::from_millis(1)); i })));
+/// });
+/// let out_rx = out.unwrap();
+///
+/// for i in 1..100 {
+///     in_tx.send(i).unwrap();
+/// }
+/// drop(in_tx);
+///
+/// let seen = out_rx.iter().collect::<Vec<_>>();
+/// assert!(seen.windows(2).all(|w| { w[0] < w[1] }));
+/// assert_eq!(seen.last(), Some(&99));
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackpressurePolicy {
+    /// Wait for the node to catch up, holding up everything upstream of it
+    Block,
+    /// Drop the value being sent
+    DropNewest,
+    /// Drop the oldest waiting value to make room for the one being sent
+    DropOldest,
+    /// Keep only the latest value waiting to be handled
+    Latest,
+}
+
+/// What to do when a node panics
+///
+/// In every case the panic is reported through `TopologyHandle::errors`.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PanicPolicy {
+    /// Stop the whole topology, as if `TopologyHandle::stop` was called
+    Shutdown,
+    /// Keep the node running with the state it had before the failing event.
+    /// Stateful nodes (such as `fold`) re-emit that state as a change so nodes
+    /// downstream are re-synchronized; for stateless nodes this is the same
+    /// as `Skip`
+    Restart,
+    /// Keep the node running and treat the failing event as unchanged
+    Skip,
 }
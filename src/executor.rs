@@ -0,0 +1,338 @@
+use std::any::Any;
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::*;
+use std::sync::atomic::*;
+
+use primitives::input::{RunInput, Dispatch, Poll};
+
+/// How many events a pool can have waiting before it stops accepting data
+/// from its inputs
+///
+const POOL_QUEUE_LIMIT: usize = 1024;
+
+/// How a topology's nodes are run
+///
+/// Whichever executor is used, events are processed in the same order and
+/// nodes see the same values; executors only differ in how many threads
+/// they use and how much work can happen at once.
+///
+/// # Example
+///
+/// ```
+/// use std::default::Default;
+/// use std::sync::mpsc::*;
+/// use cfrp::*;
+///
+/// let mut config: Config = Default::default();
+/// config.executor = Executor::Pool(4);
+///
+/// let (tx, rx) = channel();
+/// let mut out = None;
+///
+/// spawn_topology(config, |t| {
+///     let input = t.listen(1, rx);
+///
+///     out = Some(t.output(
+///         input.clone().lift2(input.lift(|i| { i * 10 }), |i, j| { *i + *j })
+///     ));
+/// });
+///
+/// let out_rx = out.unwrap();
+/// assert_eq!(out_rx.recv().unwrap(), 11);
+///
+/// tx.send(2).unwrap();
+/// assert_eq!(out_rx.recv().unwrap(), 22);
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Executor {
+    /// Run each node in its own thread, with channels between them.  Every
+    /// node can work on a different event at the same time, but large
+    /// topologies need a lot of threads
+    ThreadPerNode,
+    /// Run nodes on a fixed number of worker threads.  Nodes are handed
+    /// events through queues bounded by `Config::buffer_size`; while one of
+    /// them is full, or too many events are waiting overall, inputs are left
+    /// unread until the topology catches up
+    Pool(usize),
+    /// Run every node on a single worker thread, processing each event
+    /// completely before the next one is read from an input.  This includes
+    /// nodes upstream of `Builder::async`, so slow nodes hold up the whole
+    /// topology
+    Cooperative,
+}
+
+impl Executor {
+    /// The number of worker threads used by the executor, or `None` if each
+    /// node gets a thread of its own
+    ///
+    pub fn workers(&self) -> Option<usize> {
+        match *self {
+            Executor::ThreadPerNode => None,
+            Executor::Pool(n) => Some(if n > 0 { n } else { 1 }),
+            Executor::Cooperative => Some(1),
+        }
+    }
+
+    fn queue_limit(&self) -> usize {
+        match *self {
+            Executor::Cooperative => 1,
+            _ => POOL_QUEUE_LIMIT,
+        }
+    }
+}
+
+/// Work which can be queued on a `Scheduler`
+///
+pub trait Job: Send + Sync {
+    fn run(&self);
+}
+
+/// Hands out work to the worker threads of a topology
+///
+/// Only used by the `Pool` and `Cooperative` executors.  Each topology gets
+/// its own scheduler when its `Builder` is created.
+///
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    jobs: Mutex<Jobs>,
+    ready: Condvar,
+    pending: AtomicUsize,
+    full: AtomicUsize,
+}
+
+struct Jobs {
+    queue: VecDeque<Arc<Job>>,
+    // Set when an input may have data waiting, so the worker reading from
+    // the inputs doesn't wait before reading them again
+    woken: bool,
+}
+
+impl Scheduler {
+    /// Create a new scheduler
+    ///
+    pub fn new() -> Scheduler {
+        Scheduler {
+            inner: Arc::new(SchedulerInner {
+                jobs: Mutex::new(Jobs {
+                    queue: VecDeque::new(),
+                    woken: false,
+                }),
+                ready: Condvar::new(),
+                pending: AtomicUsize::new(0),
+                full: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Queue `job` to be run by a worker
+    ///
+    pub fn schedule(&self, job: Arc<Job>) {
+        self.inner.jobs.lock().unwrap().queue.push_back(job);
+        self.inner.ready.notify_one();
+    }
+
+    /// Wake the workers because an input may have data waiting, or the
+    /// topology has been stopped
+    ///
+    pub fn wake(&self) {
+        self.inner.jobs.lock().unwrap().woken = true;
+        self.inner.ready.notify_all();
+    }
+
+    /// Record that an event is waiting to be handled
+    ///
+    pub fn queued(&self) {
+        self.inner.pending.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Record that a waiting event has been handled
+    ///
+    pub fn handled(&self) {
+        self.inner.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// The number of events waiting to be handled
+    ///
+    pub fn pending(&self) -> usize {
+        self.inner.pending.load(Ordering::SeqCst)
+    }
+
+    /// Record that a mailbox has filled up, inputs aren't read until it has
+    /// been drained
+    ///
+    pub fn filled(&self) {
+        self.inner.full.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Record that a full mailbox has room again
+    ///
+    pub fn drained(&self) {
+        self.inner.full.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Run queued jobs on the calling thread until there are none left
+    ///
+    pub fn run_queued(&self) {
+        loop {
+            let job = self.inner.jobs.lock().unwrap().queue.pop_front();
+            match job {
+                Some(job) => job.run(),
+                None => return,
+            }
+        }
+    }
+
+    /// Run queued jobs and read from `inputs` until the topology is stopped
+    /// and every waiting event has been handled
+    ///
+    /// Idle workers sleep until a job is scheduled or the scheduler is
+    /// woken, or until the time given by an input which is waiting on a
+    /// clock.
+    ///
+    pub fn work(&self, executor: Executor, inputs: &Mutex<InputSet>, no_ops: &Dispatch, stopped: &AtomicBool) {
+        let limit = executor.queue_limit();
+
+        loop {
+            // Events which have already been let in are handled before any
+            // more are read.  A job may send data to an input, like the
+            // pusher of a timed input, so it counts as waking the inputs.
+            let job = self.inner.jobs.lock().unwrap().queue.pop_front();
+            if let Some(job) = job {
+                job.run();
+                self.inner.jobs.lock().unwrap().woken = true;
+                continue
+            }
+
+            // Only one worker reads from the inputs at a time, the others
+            // have nothing to do until it has, and leave waiting on the
+            // inputs to it
+            let mut timeout = None;
+            let mut polling = false;
+            if self.pending() < limit && self.inner.full.load(Ordering::SeqCst) == 0 {
+                if let Ok(mut inputs) = inputs.try_lock() {
+                    self.inner.jobs.lock().unwrap().woken = false;
+                    polling = true;
+
+                    match inputs.poll(no_ops, stopped) {
+                        Poll::Ready => continue,
+                        Poll::Idle(wait) => timeout = wait,
+                        Poll::Done => {},
+                    }
+                }
+            }
+
+            if stopped.load(Ordering::SeqCst) && no_ops.exited() && self.pending() == 0 {
+                debug!("Scheduler::work stopped, exiting");
+                self.inner.ready.notify_all();
+                return
+            }
+
+            // Stopping wakes the workers, so the topology may have been
+            // stopped since it was checked above
+            let jobs = self.inner.jobs.lock().unwrap();
+            let exiting = stopped.load(Ordering::SeqCst) && no_ops.exited() && self.pending() == 0;
+            if !jobs.queue.is_empty() || (polling && jobs.woken) || exiting { continue }
+
+            // The lock is taken again at the top of the loop
+            match timeout {
+                Some(timeout) => drop(self.inner.ready.wait_timeout(jobs, timeout)),
+                None => drop(self.inner.ready.wait(jobs)),
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Scheduler {
+        Scheduler::new()
+    }
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "Scheduler")
+    }
+}
+
+/// The inputs of a topology run by a `Scheduler`
+///
+/// Inputs are read from in turn, so a busy input can't keep the others from
+/// being read.
+///
+pub struct InputSet {
+    inputs: Vec<Option<Box<RunInput>>>,
+    next: usize,
+}
+
+impl InputSet {
+    pub fn new(inputs: Vec<Box<RunInput>>) -> InputSet {
+        InputSet {
+            inputs: inputs.into_iter().map(Some).collect(),
+            next: 0,
+        }
+    }
+
+    /// Dispatch `a` from the input at `idx`, returns false if the input has
+    /// exited
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no input at `idx`, or if it can't be pushed `a`
+    ///
+    pub fn push(&mut self, idx: usize, a: Box<Any + Send>, no_ops: &Dispatch, stopped: &AtomicBool) -> bool {
+        match self.inputs.get_mut(idx) {
+            Some(&mut Some(ref mut input)) => input.push(idx, a, no_ops, stopped),
+            Some(&mut None) => false,
+            None => panic!("topology has no input {}", idx),
+        }
+    }
+
+    /// Dispatch at most one event from the inputs
+    ///
+    /// Returns `Poll::Idle` with the soonest time any of the inputs needs to
+    /// be polled again if none of them had an event waiting, or `Poll::Done`
+    /// once they've all exited.
+    ///
+    pub fn poll(&mut self, no_ops: &Dispatch, stopped: &AtomicBool) -> Poll {
+        let len = self.inputs.len();
+        let mut idle = None;
+        let mut done = true;
+
+        for i in 0..len {
+            let idx = (self.next + i) % len;
+
+            let polled = match self.inputs[idx] {
+                Some(ref mut input) => input.poll(idx, no_ops, stopped),
+                None => continue,
+            };
+
+            match polled {
+                Poll::Ready => {
+                    self.next = idx + 1;
+                    return Poll::Ready
+                },
+                Poll::Idle(wait) => {
+                    done = false;
+                    idle = match (idle, wait) {
+                        (Some(a), Some(b)) => Some(cmp::min(a, b)),
+                        (a, b) => a.or(b),
+                    };
+                },
+                Poll::Done => self.inputs[idx] = None,
+            }
+        }
+
+        if done {
+            Poll::Done
+        } else {
+            Poll::Idle(idle)
+        }
+    }
+}
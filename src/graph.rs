@@ -0,0 +1,237 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::sync::atomic::*;
+
+// Source of the ids given to shared nodes
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A description of a node in a topology, along with the nodes it receives
+/// data from
+///
+/// Nodes are returned by `Signal::describe` and `Run::describe`, and can be
+/// rendered with `Builder::to_dot`.  They're also used to work out which
+/// inputs can have their events handled independently of each other, see
+/// `components`.
+///
+#[derive(Clone, Debug)]
+pub struct Node {
+    /// The kind of node, such as "lift" or "fold"
+    pub kind: &'static str,
+    /// The name given to the node with `SignalExt::named`
+    pub name: Option<String>,
+    /// True if the node's value never changes
+    pub constant: bool,
+    /// The nodes this node receives data from
+    pub parents: Vec<Arc<Node>>,
+    id: Option<usize>,
+    // Set if the node's parents aren't known
+    opaque: bool,
+}
+
+impl Node {
+    /// Describe a node which is only reachable from one child
+    ///
+    pub fn new(kind: &'static str, name: Option<String>, constant: bool, parents: Vec<Node>) -> Node {
+        Node {
+            kind,
+            name,
+            constant,
+            parents: parents.into_iter().map(Arc::new).collect(),
+            id: None,
+            opaque: false,
+        }
+    }
+
+    /// Describe a node which doesn't say what it receives data from
+    ///
+    /// Returned by the default `Signal::describe` and `Run::describe`.  A
+    /// topology containing one can't tell which inputs it depends on, so
+    /// every input is treated as being joined with every other.
+    ///
+    pub fn opaque() -> Node {
+        let mut node = Node::new("unknown", None, false, vec![]);
+        node.opaque = true;
+        node
+    }
+
+    /// Describe a node which can be reachable from several children, such as
+    /// a fork
+    ///
+    /// Clones of a shared node are the same node, and are only rendered once.
+    ///
+    pub fn shared(kind: &'static str, name: Option<String>, constant: bool, parents: Vec<Node>) -> Node {
+        let mut node = Node::new(kind, name, constant, parents);
+        node.id = Some(NEXT_ID.fetch_add(1, Ordering::SeqCst));
+        node
+    }
+
+    /// The node's id, if it's shared
+    ///
+    pub fn id(&self) -> Option<usize> {
+        self.id
+    }
+
+    fn is_input(&self) -> bool {
+        self.parents.is_empty() && !self.constant
+    }
+}
+
+/// Render the nodes reachable from `roots` as a DOT graph
+///
+/// Inputs are drawn as boxes, forks as diamonds and `async` boundaries,
+/// including `debounce`, `throttle` and `delay`, as double boxes with dashed
+/// edges leaving them.  Constant nodes, which are never run, are drawn in
+/// grey.
+///
+pub fn to_dot(roots: &[Node]) -> String {
+    let mut dot = Dot {
+        out: String::new(),
+        shared: HashMap::new(),
+        next: 0,
+    };
+
+    dot.out.push_str("digraph topology {\n");
+    for root in roots.iter() {
+        dot.node(root);
+    }
+    dot.out.push_str("}\n");
+
+    dot.out
+}
+
+/// Group the inputs described by `inputs` by the runners described by
+/// `roots` which receive from them, returns the group of each input
+///
+/// Inputs are in the same group if a runner receives from both, directly or
+/// through other inputs in the group.  Inputs must be shared nodes, and
+/// walking up from a root stops at them, so the subgraph of an `async` node
+/// is only reachable from the runner pushing it rather than from the
+/// consumers of its results.
+///
+/// If an input isn't a shared node, or a root reaches an opaque node, every
+/// input is put in the same group.
+///
+pub fn components(inputs: &[Node], roots: &[Node]) -> Vec<usize> {
+    let ids = inputs.iter().enumerate()
+        .filter_map(|(i, input)| input.id.map(|id| (id, i)))
+        .collect::<HashMap<usize, usize>>();
+    let mut opaque = ids.len() < inputs.len();
+
+    let mut groups = (0..inputs.len()).collect::<Vec<usize>>();
+    for root in roots.iter() {
+        let mut reached = Vec::new();
+        let mut seen = HashSet::new();
+        reachable(root, true, &ids, &mut seen, &mut reached, &mut opaque);
+
+        for pair in reached.windows(2) {
+            let (a, b) = (find(&mut groups, pair[0]), find(&mut groups, pair[1]));
+            groups[a] = b;
+        }
+    }
+
+    if opaque {
+        return vec![0; inputs.len()]
+    }
+
+    (0..inputs.len()).map(|i| find(&mut groups, i)).collect()
+}
+
+// Adds the inputs `node` receives from to `reached`, and sets `opaque` if an
+// opaque node is reachable from it
+fn reachable(node: &Node, root: bool, ids: &HashMap<usize, usize>, seen: &mut HashSet<usize>, reached: &mut Vec<usize>, opaque: &mut bool) {
+    if node.opaque {
+        *opaque = true;
+    }
+
+    if let Some(id) = node.id {
+        if !seen.insert(id) { return }
+
+        if !root {
+            if let Some(i) = ids.get(&id) {
+                reached.push(*i);
+                return
+            }
+        }
+    }
+
+    for parent in node.parents.iter() {
+        reachable(parent, false, ids, seen, reached, opaque);
+    }
+}
+
+// True if `node` re-enters the topology as an input, like `async`
+fn is_boundary(node: &Node) -> bool {
+    matches!(node.kind, "async" | "debounce" | "throttle" | "delay")
+}
+
+// The group `i` belongs to
+fn find(groups: &mut [usize], i: usize) -> usize {
+    let mut i = i;
+    while groups[i] != i {
+        groups[i] = groups[groups[i]];
+        i = groups[i];
+    }
+    i
+}
+
+struct Dot {
+    out: String,
+    shared: HashMap<usize, usize>,
+    next: usize,
+}
+
+impl Dot {
+    // Writes `node` and everything upstream of it, returns the node's number
+    fn node(&mut self, node: &Node) -> usize {
+        if let Some(id) = node.id {
+            if let Some(n) = self.shared.get(&id) {
+                return *n
+            }
+        }
+
+        let parents = node.parents.iter().map(|p| self.node(p)).collect::<Vec<usize>>();
+
+        let n = self.next;
+        self.next += 1;
+        if let Some(id) = node.id {
+            self.shared.insert(id, n);
+        }
+
+        let mut attrs = match node.name {
+            Some(ref name) => format!("label=\"{} ({})\"", name.replace("\"", "\\\""), node.kind),
+            None => format!("label=\"{}\"", node.kind),
+        };
+        if node.is_input() {
+            attrs.push_str(", shape=box");
+        }
+        if node.kind == "fork" {
+            attrs.push_str(", shape=diamond");
+        }
+        if is_boundary(node) {
+            attrs.push_str(", shape=box, peripheries=2");
+        }
+        if node.constant {
+            attrs.push_str(", color=grey, fontcolor=grey");
+        }
+        writeln!(self.out, "    n{} [{}];", n, attrs).unwrap();
+
+        for (parent, p) in node.parents.iter().zip(parents) {
+            let mut attrs = Vec::new();
+            if is_boundary(parent) {
+                attrs.push("style=dashed");
+            }
+            if parent.constant {
+                attrs.push("color=grey");
+            }
+
+            if attrs.is_empty() {
+                writeln!(self.out, "    n{} -> n{};", p, n).unwrap();
+            } else {
+                writeln!(self.out, "    n{} -> n{} [{}];", p, n, attrs.join(", ")).unwrap();
+            }
+        }
+
+        n
+    }
+}
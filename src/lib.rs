@@ -16,7 +16,9 @@
 //! 
 //! // Topologies are statically defined, run-once structures.  Due to how
 //! // concurrency is handled, changes to the graph structure can cause
-//! // inconsistencies in the data processing
+//! // inconsistencies in the data processing; the only change allowed once
+//! // a topology is running is attaching subgraphs to the branches it was
+//! // built with, see `TopologyHandle::attach`
 //! // 
 //! spawn_topology(Default::default(), |t| {
 //! 
@@ -57,13 +59,25 @@ pub mod primitives;
 mod signal_ext;
 mod topology;
 mod builder;
+mod clock;
 mod config;
+mod executor;
+mod graph;
+mod metrics;
+mod supervisor;
+mod test_topology;
 mod value;
 
 pub use signal_ext::SignalExt;
-pub use topology::{Topology, TopologyHandle};
+pub use topology::{Topology, TopologyHandle, Attachment, DropPolicy};
 pub use builder::Builder;
-pub use config::Config;
+pub use clock::{Clock, SystemClock, VirtualClock};
+pub use config::{Config, Overrides, BackpressurePolicy, PanicPolicy};
+pub use executor::{Executor, Scheduler};
+pub use graph::Node;
+pub use metrics::{Metrics, NodeMetrics, NodeRole, NodeStats};
+pub use supervisor::{Supervisor, NodeError, NodeErrorKind};
+pub use test_topology::TestTopology;
 pub use value::Value;
 
 /// Container for data as it flows across the topology
@@ -88,6 +102,13 @@ impl<A> SignalType<A> {
             SignalType::Dynamic(v) => v,
         }
     }
+
+    fn is_constant(&self) -> bool {
+        match *self {
+            SignalType::Constant(_) => true,
+            SignalType::Dynamic(_) => false,
+        }
+    }
 }
 
 /// Types which can serve as a data source
@@ -105,7 +126,15 @@ A: 'static + Send + Clone,
     fn initial(&self) -> SignalType<A>;
 
     // Called at compile time when a donstream process is run
-    fn push_to(self: Box<Self>, Option<Box<Push<A>>>);
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>);
+
+    // Describes the signal and its parents, used by `Builder::to_dot` and to
+    // find which inputs are joined.  Signals which aren't described are
+    // assumed to join every input, so the topology can't route events from
+    // unrelated inputs to separate groups of nodes
+    fn describe(&self) -> Node {
+        Node::opaque()
+    }
 }
 
 impl<A> Signal<A> for Box<Signal<A>> where
@@ -123,16 +152,31 @@ A: 'static + Send + Clone,
         (**self).initial()
     }
 
-    fn push_to(self: Box<Self>, target: Option<Box<Push<A>>>) {
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
         (*self).push_to(target)
     }
+
+    fn describe(&self) -> Node {
+        (**self).describe()
+    }
 }
 impl<A> SignalExt<A> for Box<Signal<A>> where
 A: 'static + Send + Clone,
 {}
 
+/// Signals which keep a Config of their own, and so can be named with
+/// `SignalExt::named`
+///
+pub trait Configurable {
+    // Returns the signal's Config for modification
+    fn config_mut(&mut self) -> &mut Config;
+}
+
 /// Types which can receive incoming data from other signals
 ///
+/// Targets passed to `Signal::push_to` are boxed with `Send`, as executors
+/// such as `Executor::Pool` push to them from any of their workers.
+///
 pub trait Push<A> {
     fn push(&mut self, Event<A>);
 }
@@ -141,10 +185,27 @@ pub trait Push<A> {
 ///
 /// Linear transformations are combined into thread-local function
 /// compositions - concurrency only applies to forking/merging transformations.
-/// `Run` is required for the 'tip' of each linear transformation.
+/// Signals which are added to a topology but only consumed once are composed
+/// the same way.  `Run` is required for the 'tip' of each linear
+/// transformation.
 ///
 pub trait Run: Send {
-    fn run(mut self: Box<Self>);
+    fn run(self: Box<Self>);
+
+    // The name of the signal the runner runs, if it has been named
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    // Describes the runner and everything upstream of it, like
+    // `Signal::describe`
+    fn describe(&self) -> Node {
+        Node::opaque()
+    }
+
+    // Called once every runner has been built, before any are run.  Returns
+    // None if the runner's work has been handed to another runner
+    fn fuse(self: Box<Self>) -> Option<Box<Run>>;
 }
 
 /// Construct a new topology and run it
@@ -168,7 +229,7 @@ pub fn spawn_topology<F>(config: Config, f: F) -> TopologyHandle where
 {
     let builder = Builder::new(config);
     f(&builder);
-    Topology::new(builder.inputs.into_inner(), builder.runners.into_inner()).run()
+    builder.build().run()
 }
 
 #[cfg(test)] 
@@ -176,9 +237,10 @@ mod test {
     extern crate env_logger;
 
     use std::default::Default;
+    use std::sync::Arc;
     use std::sync::mpsc::*;
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use rand;
 
@@ -335,6 +397,1180 @@ mod test {
         assert!(first != second);
     }
 
+    #[test]
+    fn merge_prefers_left() {
+        let (tx, rx) = channel();
+        let (out_tx, out_rx) = channel();
+
+        spawn_topology(Default::default(), move |t| {
+            let a = t.listen(1 << 0, rx);
+
+            a.clone()
+                .lift(|i| { i | (1 << 1) })
+                .merge(a.lift(|i| { i | (1 << 2) }))
+                .lift(move |i| { out_tx.send(i).unwrap(); })
+                .add_to(t);
+        });
+
+        // Initial value
+        assert_eq!(out_rx.recv().unwrap(), (1 << 0) | (1 << 1));
+
+        // Both sides change in the same event
+        tx.send(1 << 3).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (1 << 3) | (1 << 1));
+    }
+
+    #[test]
+    fn keep_if_skips_downstream() {
+        let (tx, rx) = channel();
+        let (out_tx, out_rx) = channel();
+
+        spawn_topology(Default::default(), move |t| {
+            t.listen(0, rx)
+                .keep_if(0, |i| { i % 2 == 0 })
+                .fold(0, |count, _| { count + 1 })
+                .lift(move |i| { out_tx.send(i).unwrap(); })
+                .add_to(t);
+        });
+
+        // Initial value
+        assert_eq!(out_rx.recv().unwrap(), 1);
+
+        // Filtered values don't reach the fold
+        tx.send(1).unwrap();
+        tx.send(3).unwrap();
+        tx.send(4).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 2);
+    }
+
+    #[test]
+    fn drop_repeats_skips_downstream() {
+        let (tx, rx) = channel();
+        let (out_tx, out_rx) = channel();
+
+        spawn_topology(Default::default(), move |t| {
+            t.listen(0, rx)
+                .lift(|i| { i / 10 })
+                .drop_repeats()
+                .fold(0, |count, _| { count + 1 })
+                .lift(move |i| { out_tx.send(i).unwrap(); })
+                .add_to(t);
+        });
+
+        // Initial value
+        assert_eq!(out_rx.recv().unwrap(), 1);
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        tx.send(10).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 2);
+
+        tx.send(11).unwrap();
+        tx.send(0).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 3);
+    }
+
+    #[test]
+    fn sample_on_constant() {
+        let (tick_tx, tick_rx) = channel();
+        let (out_tx, out_rx) = channel();
+
+        spawn_topology(Default::default(), move |t| {
+            t.value(1 << 0)
+                .sample_on(t.listen((), tick_rx))
+                .fold(0, |count, _| { count + 1 })
+                .lift(move |i| { out_tx.send(i).unwrap(); })
+                .add_to(t);
+        });
+
+        // Initial value
+        assert_eq!(out_rx.recv().unwrap(), 1);
+
+        // Constant values are still sampled on each tick
+        tick_tx.send(()).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 2);
+        tick_tx.send(()).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 3);
+    }
+
+    #[test]
+    fn lift8_changed_values() {
+        let (tx, rx) = channel();
+        let (out_tx, out_rx) = channel();
+
+        spawn_topology(Default::default(), move |t| {
+            let a = t.listen(1 << 0, rx);
+
+            a.clone().lift8(
+                t.value(1 << 1),
+                t.value(1 << 2),
+                t.value(1 << 3),
+                t.value(1 << 4),
+                t.value(1 << 5),
+                t.value(1 << 6),
+                a.lift(|i| { i << 7 }),
+                move |a1, a2, a3, a4, a5, a6, a7, a8| {
+                    let changed = match (a1, a8) {
+                        (Value::Changed(i), Value::Changed(j)) => i | j,
+                        _ => 0,
+                    };
+                    out_tx.send(changed | *a2 | *a3 | *a4 | *a5 | *a6 | *a7).unwrap();
+                })
+                .add_to(t);
+        });
+
+        // Initial value
+        assert_eq!(out_rx.recv().unwrap(), 0b11111111);
+
+        tx.send(1 << 8).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (1 << 8) | (1 << 15) | 0b01111110);
+    }
+
+    #[test]
+    fn lift3_branch_values() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+
+            // The last parent is initialized first, so the fork sends to the
+            // join's inputs in a different order than it reads them
+            let c = a.clone().lift(|i| { i << 2 });
+            out = Some(t.output(a.clone().lift3(a.lift(|i| { i << 1 }), c, |i, j, k| { *i | *j | *k })));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..10 {
+            tx.send(1 << (i * 3)).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), 0b111 << (i * 3));
+        }
+    }
+
+    #[test]
+    fn lift4_branch_values() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+            out = Some(t.output(a.clone().lift4(
+                a.clone().lift(|i| { i << 1 }),
+                t.value(0),
+                a.lift(|i| { i << 3 }),
+                |i, j, k, l| { *i | *j | *k | *l },
+            )));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..10 {
+            tx.send(1 << (i * 4)).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), 0b1011 << (i * 4));
+        }
+    }
+
+    #[test]
+    fn lift5_branch_values() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+            out = Some(t.output(a.clone().lift5(
+                a.clone().lift(|i| { i << 1 }),
+                a.clone().lift(|i| { i << 2 }),
+                a.clone().lift(|i| { i << 3 }),
+                a.lift(|i| { i << 4 }),
+                |i, j, k, l, m| { *i | *j | *k | *l | *m },
+            )));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..10 {
+            tx.send(1 << (i * 5)).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), 0b11111 << (i * 5));
+        }
+    }
+
+    #[test]
+    fn lift6_branch_values() {
+        let (tx, rx) = channel();
+        let (other_tx, other_rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+            let b = t.listen(0usize, other_rx);
+
+            // Only the changed inputs are counted
+            let changed = |v: Value<usize>| {
+                match v {
+                    Value::Changed(_) => 1,
+                    Value::Unchanged(_) => 0,
+                }
+            };
+            out = Some(t.output(a.clone().lift6(b.clone(), a.clone(), b.clone(), a, b, move |i, j, k, l, m, n| {
+                (changed(i) + changed(k) + changed(m), changed(j) + changed(l) + changed(n))
+            })));
+        });
+        let out_rx = out.unwrap();
+
+        // Initial values count as changed
+        assert_eq!(out_rx.recv().unwrap(), (3, 3));
+
+        tx.send(1).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (3, 0));
+
+        other_tx.send(1).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (0, 3));
+    }
+
+    #[test]
+    fn lift7_branch_values() {
+        let (tx, rx) = channel();
+        let mut out = None;
+        let mut config: Config = Default::default();
+        config.executor = Executor::Pool(2);
+
+        spawn_topology(config, |t| {
+            let a = t.listen(0usize, rx);
+            out = Some(t.output(a.clone().lift7(
+                a.clone().lift(|i| { i << 1 }),
+                a.clone().lift(|i| { i << 2 }),
+                a.clone().lift(|i| { i << 3 }),
+                a.clone().lift(|i| { i << 4 }),
+                a.clone().lift(|i| { i << 5 }),
+                a.lift(|i| { i << 6 }),
+                |i, j, k, l, m, n, o| { *i | *j | *k | *l | *m | *n | *o },
+            )));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..8 {
+            tx.send(1 << (i * 7)).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), 0b1111111 << (i * 7));
+        }
+    }
+
+    #[test]
+    fn lift_all_branch_values() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+            let signals = (0..16).map(|n| -> Box<Signal<usize>> {
+                Box::new(a.clone().lift(move |i| { i * n }))
+            }).collect();
+
+            out = Some(t.output(t.lift_all(signals, |vs| {
+                vs.into_iter().map(|v| *v).sum::<usize>()
+            })));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..10 {
+            tx.send(i).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), i * (0..16).sum::<usize>());
+        }
+    }
+
+    #[test]
+    fn join_runs_parents_in_its_thread() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        let name = || { thread::current().name().unwrap().to_string() };
+
+        spawn_topology(Default::default(), |t| {
+            let a = t.listen(0usize, rx);
+            let left = a.clone().named("left").lift(move |_| { name() });
+            let right = a.named("right").lift(move |_| { name() });
+
+            out = Some(t.output(left.lift2(right, move |l, r| { ((*l).clone(), (*r).clone(), name()) })));
+        });
+        let out_rx = out.unwrap();
+
+        tx.send(1).unwrap();
+        let (l, r, joined) = out_rx.recv().unwrap();
+        assert_eq!(l, joined);
+        assert_eq!(r, joined);
+    }
+
+    #[test]
+    fn output_only_changes() {
+        let (tx, rx) = channel();
+        let (other_tx, other_rx) = channel();
+        let mut out = None;
+
+        spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.listen(0, rx).keep_if(0, |i| { i % 2 == 0 })));
+            t.add(t.listen(0, other_rx));
+        });
+        let out_rx = out.unwrap();
+
+        tx.send(1).unwrap();
+        other_tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        drop(tx);
+
+        assert_eq!(out_rx.iter().collect::<Vec<usize>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn stop_and_join() {
+        let (l_tx, l_rx) = channel();
+        let (r_tx, r_rx) = channel();
+        let mut out = None;
+
+        let mut handle = spawn_topology(Default::default(), |t| {
+            let l = t.listen(1 << 0, l_rx);
+            let r = t.listen(1 << 1, r_rx);
+
+            out = Some(t.output(l.lift2(r, |i, j| { *i | *j }).add_to(t)));
+        });
+        let out_rx = out.unwrap();
+
+        assert_eq!(out_rx.recv().unwrap(), (1 << 0) | (1 << 1));
+
+        l_tx.send(1 << 2).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (1 << 2) | (1 << 1));
+
+        assert_eq!(handle.stop_and_join(Duration::from_secs(5)), Ok(()));
+
+        // Inputs and outputs are closed once the topology has exited, even
+        // inputs which were waiting for data
+        assert!(out_rx.recv().is_err());
+        assert!(r_tx.send(1 << 3).is_err());
+    }
+
+    #[test]
+    fn stop_while_dispatching() {
+        // Stopping mustn't miss groups which inputs are dispatching to
+        for _ in 0..20 {
+            let (l_tx, l_rx) = channel();
+            let (r_tx, r_rx) = channel();
+
+            let mut handle = spawn_topology(Default::default(), |t| {
+                let l = t.listen(0usize, l_rx);
+                let r = t.listen(0usize, r_rx);
+                t.add(l.lift2(r, |i, j| { *i + *j }));
+            });
+
+            let senders = vec![l_tx, r_tx].into_iter().map(|tx| {
+                thread::spawn(move || {
+                    let mut i = 0;
+                    while tx.send(i).is_ok() && i < 100_000 { i += 1; }
+                })
+            }).collect::<Vec<_>>();
+            thread::sleep(Duration::from_millis(5));
+
+            assert_eq!(handle.stop_and_join(Duration::from_secs(5)), Ok(()));
+            for sender in senders {
+                sender.join().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn stop_and_join_blocked_node() {
+        let (tx, rx) = channel();
+        let (release_tx, release_rx) = channel();
+
+        let mut handle = spawn_topology(Default::default(), |t| {
+            t.add(t.listen(0usize, rx).lift(move |i| {
+                if i > 0 { release_rx.recv().unwrap(); }
+                i
+            }));
+        });
+
+        // The lift blocks on the first value, and the input on the second
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        let start = Instant::now();
+        assert!(handle.stop_and_join(Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Once the node is unblocked the topology exits as normal
+        release_tx.send(()).unwrap();
+        let _ = release_tx.send(());
+        handle.join();
+    }
+
+    #[test]
+    fn join_after_disconnect() {
+        let (tx, rx) = channel();
+        let (_other_tx, other_rx) = channel();
+
+        let mut handle = spawn_topology(Default::default(), |t| {
+            t.add(t.listen(0, rx).lift(|i| { i + 1 }));
+            t.add(t.listen(0, other_rx));
+        });
+
+        tx.send(1).unwrap();
+        drop(tx);
+
+        // Disconnecting one input stops the whole topology
+        handle.join();
+    }
+
+    #[test]
+    fn panic_skip() {
+        let (tx, rx) = channel();
+        let mut out = None;
+        let mut config: Config = Default::default();
+        config.panic_policy = PanicPolicy::Skip;
+
+        let handle = spawn_topology(config, |t| {
+            out = Some(t.output(t.listen(1, rx).lift(|i: usize| { 10 / i })));
+        });
+        let out_rx = out.unwrap();
+
+        tx.send(0).unwrap();
+        tx.send(2).unwrap();
+
+        assert_eq!(handle.errors().recv().unwrap().node, "lift");
+        assert_eq!(out_rx.recv().unwrap(), 10);
+        assert_eq!(out_rx.recv().unwrap(), 5);
+    }
+
+    #[test]
+    fn panic_restart_fold() {
+        let (tx, rx) = channel();
+        let mut out = None;
+        let mut config: Config = Default::default();
+        config.panic_policy = PanicPolicy::Restart;
+
+        let handle = spawn_topology(config, |t| {
+            out = Some(t.output(t.listen(1, rx).fold(0, |sum, i: usize| {
+                if i == 0 { panic!("zero") }
+                sum + i
+            })));
+        });
+        let out_rx = out.unwrap();
+
+        tx.send(1).unwrap();
+        tx.send(0).unwrap();
+        tx.send(2).unwrap();
+
+        assert_eq!(
+            handle.errors().recv().unwrap(),
+            NodeError { node: "fold".to_string(), message: "zero".to_string(), kind: NodeErrorKind::Panic }
+        );
+        // The fold's state is re-emitted in place of the failed event
+        assert_eq!(out_rx.iter().take(4).collect::<Vec<usize>>(), vec![1, 2, 2, 4]);
+    }
+
+    #[test]
+    fn try_lift_initial_error() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        let handle = spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.listen("x", rx).try_lift(7, |s| s.parse::<usize>()).named("parse")));
+        });
+        let out_rx = out.unwrap();
+
+        // The default stands in for the failed initial value, and the error
+        // is reported under the node's name
+        assert_eq!(out_rx.recv().unwrap(), 7);
+        let error = handle.errors().recv().unwrap();
+        assert_eq!((error.node.as_str(), error.kind), ("parse", NodeErrorKind::Failed));
+
+        tx.send("3").unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 3);
+        assert!(handle.errors().try_recv().is_err());
+    }
+
+    #[test]
+    fn try_fold_errors_dont_stop_topology() {
+        let (tx, rx) = channel();
+        let mut out = None;
+
+        // Panics would stop the topology with the default policy
+        let handle = spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.listen(0u8, rx).try_fold(0u8, |sum, i| { sum.checked_add(i).ok_or(i) })));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        tx.send(200).unwrap();
+        tx.send(100).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 200);
+        assert_eq!(handle.errors().recv().unwrap().message, "100");
+
+        tx.send(50).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 250);
+    }
+
+    fn joins_and_async_with(executor: Executor) {
+        let (tx, rx) = channel();
+        let (other_tx, other_rx) = channel();
+        let mut outputs = None;
+        let mut config: Config = Default::default();
+        config.executor = executor;
+
+        let mut handle = spawn_topology(config, |t| {
+            let input = t.listen(0, rx);
+            let doubled = input.clone().lift(|i| { i * 2 }).async(t);
+            let sum = input
+                .lift2(t.listen(0, other_rx), |i, j| { *i + *j })
+                .fold(0, |acc, i| { acc + i });
+
+            outputs = Some((t.output(sum), t.output(doubled)));
+        });
+        let (sum_rx, doubled_rx) = outputs.unwrap();
+
+        assert_eq!(sum_rx.recv().unwrap(), 0);
+        assert_eq!(doubled_rx.recv().unwrap(), 0);
+
+        tx.send(1).unwrap();
+        assert_eq!(sum_rx.recv().unwrap(), 1);
+        assert_eq!(doubled_rx.recv().unwrap(), 2);
+
+        other_tx.send(10).unwrap();
+        assert_eq!(sum_rx.recv().unwrap(), 12);
+
+        assert!(handle.stop_and_join(Duration::from_secs(1)).is_ok());
+        assert!(sum_rx.recv().is_err());
+        assert!(doubled_rx.recv().is_err());
+    }
+
+    #[test]
+    fn pool_executor() {
+        joins_and_async_with(Executor::Pool(3));
+    }
+
+    #[test]
+    fn cooperative_executor() {
+        joins_and_async_with(Executor::Cooperative);
+    }
+
+    #[test]
+    fn test_topology_lockstep() {
+        let (_a_tx, a_rx) = channel();
+        let (_b_tx, b_rx) = channel();
+        let mut outputs = None;
+
+        let mut t = TestTopology::new(Default::default(), |t| {
+            let a = t.listen(1usize, a_rx);
+            let b = t.listen(2usize, b_rx);
+
+            outputs = Some((
+                t.output(a.clone().merge(b.clone())),
+                t.output(a.lift2(b, |i, j| { 100 / (*i * *j) })),
+            ));
+        });
+        let (merged_rx, lifted_rx) = outputs.unwrap();
+
+        assert_eq!(merged_rx.try_recv().unwrap(), 1);
+        assert_eq!(lifted_rx.try_recv().unwrap(), 50);
+
+        t.push(1, 5usize);
+        assert_eq!(merged_rx.try_recv().unwrap(), 5);
+        assert_eq!(lifted_rx.try_recv().unwrap(), 20);
+
+        t.push(0, 10usize);
+        assert_eq!(merged_rx.try_recv().unwrap(), 10);
+        assert_eq!(lifted_rx.try_recv().unwrap(), 2);
+
+        // Panics stop the topology by default
+        t.push(0, 0usize);
+        assert_eq!(t.errors().try_recv().unwrap().node, "lift2");
+        assert_eq!(merged_rx.try_recv().unwrap(), 0);
+        assert!(lifted_rx.try_recv().is_err());
+
+        t.push(0, 1usize);
+        assert_eq!(merged_rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(lifted_rx.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn every_virtual_clock() {
+        let clock = VirtualClock::new();
+        let mut config: Config = Default::default();
+        config.clock = Arc::new(clock.clone());
+
+        let mut out = None;
+        spawn_topology(config, |t| {
+            let start = t.config().clock.now();
+            out = Some(t.output(t.every(Duration::from_secs(60)).lift(move |tm| { tm - start })));
+        });
+        let out_rx = out.unwrap();
+
+        assert_eq!(out_rx.recv().unwrap(), Duration::from_secs(0));
+
+        // Nothing happens until the clock is moved
+        thread::sleep(Duration::from_millis(50));
+        assert!(out_rx.try_recv().is_err());
+
+        clock.advance(Duration::from_secs(150));
+        assert_eq!(out_rx.recv().unwrap(), Duration::from_secs(60));
+        assert_eq!(out_rx.recv().unwrap(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn to_dot_shares_nodes() {
+        let (_tx, rx) = channel();
+        let mut dot = String::new();
+
+        spawn_topology(Default::default(), |t| {
+            let input = t.listen(0, rx);
+            let doubled = input.clone().lift(|i| { i * 2 }).lift2(input, |i, j| { *i + *j });
+
+            t.output(doubled.async(t).lift(|i| { i + 1 }));
+            dot = t.to_dot();
+        });
+
+        // Each branch of a fork and both sides of an async are the same node
+        assert_eq!(dot.matches("label=\"listen\"").count(), 1);
+        assert_eq!(dot.matches("label=\"fork\"").count(), 2);
+        assert_eq!(dot.matches("label=\"async\"").count(), 1);
+        assert_eq!(dot.matches("style=dashed").count(), 1);
+        assert_eq!(dot.matches(" -> ").count(), 8);
+    }
+
+    #[test]
+    fn named_nodes() {
+        let (tx, rx) = channel();
+        let mut out = None;
+        let mut dot = String::new();
+
+        spawn_topology(Default::default(), |t| {
+            let parsed = t.listen(0, rx)
+                .lift(|i| { (i, thread::current().name().map(|n| n.to_string())) })
+                .named("parse")
+                .add_to(t);
+
+            out = Some(t.output(parsed));
+            dot = t.to_dot();
+        });
+        let out_rx = out.unwrap();
+
+        assert!(dot.contains("[label=\"parse (lift)\"]"));
+        out_rx.recv().unwrap();
+
+        // The lift is run by the thread of the fork it's added with
+        tx.send(1).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), (1, Some("parse".to_string())));
+    }
+
+    #[test]
+    fn metrics_count_events() {
+        let (_a_tx, a_rx) = channel();
+        let (_b_tx, b_rx) = channel();
+        let mut outputs = None;
+
+        let mut t = TestTopology::new(Default::default(), |t| {
+            let doubled = t.listen(1usize, a_rx).lift(|i| { i * 2 }).named("double");
+            outputs = Some((t.output(doubled), t.output(t.listen(0usize, b_rx))));
+        });
+
+        t.push(0, 5usize);
+        t.push(1, 1usize);
+        t.push(1, 2usize);
+
+        let stats = |t: &TestTopology| {
+            t.metrics().values().find(|n| n.name == "double" && n.role == NodeRole::Node).unwrap().clone()
+        };
+
+        // The other input doesn't share a runner, so isn't counted here
+        let double = stats(&t);
+        assert_eq!((double.changed, double.unchanged, double.exited), (1, 0, 0));
+        assert!(t.metrics().values().all(|n| n.queued == 0));
+
+        t.stop();
+        assert_eq!(stats(&t).exited, 1);
+    }
+
+    // A signal which leaves `describe` to its default
+    struct Undescribed<A>(Box<Signal<A>>) where A: 'static + Send + Clone;
+
+    impl<A> Signal<A> for Undescribed<A> where
+    A: 'static + Send + Clone,
+    {
+        fn config(&self) -> Config {
+            self.0.config()
+        }
+
+        fn init(&mut self) {
+            self.0.init()
+        }
+
+        fn initial(&self) -> SignalType<A> {
+            self.0.initial()
+        }
+
+        fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+            self.0.push_to(target)
+        }
+    }
+    impl<A> SignalExt<A> for Undescribed<A> where A: 'static + Send + Clone {}
+
+    #[test]
+    fn undescribed_signals_see_every_event() {
+        let (_a_tx, a_rx) = channel();
+        let (_b_tx, b_rx) = channel();
+        let mut outputs = None;
+
+        let mut t = TestTopology::new(Default::default(), |t| {
+            let doubled = t.listen(1usize, a_rx).lift(|i| { i * 2 }).named("double");
+            outputs = Some((t.output(Undescribed(Box::new(doubled))), t.output(t.listen(0usize, b_rx))));
+        });
+
+        t.push(0, 5usize);
+        t.push(1, 1usize);
+        t.push(1, 2usize);
+
+        // Without a description the other input can't be ruled out, so its
+        // events are sent here too
+        let double = t.metrics().values().find(|n| n.name == "double" && n.role == NodeRole::Node).unwrap().clone();
+        assert_eq!((double.changed, double.unchanged, double.exited), (1, 2, 0));
+    }
+
+    #[test]
+    fn metrics_count_unchanged_events() {
+        let (_a_tx, a_rx) = channel();
+        let (_b_tx, b_rx) = channel();
+        let mut out = None;
+
+        let mut t = TestTopology::new(Default::default(), |t| {
+            let doubled = t.listen(1usize, a_rx).lift(|i| { i * 2 }).named("double");
+            out = Some(t.output(doubled.lift2(t.listen(0usize, b_rx), |a, b| { (*a, *b) })));
+        });
+
+        t.push(0, 5usize);
+        t.push(1, 1usize);
+        t.push(1, 2usize);
+
+        let double = t.metrics().values().find(|n| n.name == "double" && n.role == NodeRole::Node).unwrap().clone();
+        assert_eq!((double.changed, double.unchanged, double.exited), (1, 2, 0));
+    }
+
+    #[test]
+    fn unchanged_only_reaches_joined_inputs() {
+        let (_a_tx, a_rx) = channel();
+        let (_b_tx, b_rx) = channel();
+        let (_c_tx, c_rx) = channel();
+        let mut outputs = None;
+
+        let mut t = TestTopology::new(Default::default(), |t| {
+            let a = t.listen(0usize, a_rx).named("a");
+            let b = t.listen(0usize, b_rx).named("b");
+            let c = t.listen(0usize, c_rx).lift(|i| { i }).named("c");
+            let acks = t.ack_value(1usize).fold(0, |n, i| { n + i });
+
+            outputs = Some((t.output(a.lift2(b, |a, b| { *a + *b })), t.output(c), t.output(acks)));
+        });
+        let (ab_rx, c_rx, acks_rx) = outputs.unwrap();
+
+        t.push(0, 1usize);
+        t.push(2, 10usize);
+        t.push(1, 2usize);
+
+        assert_eq!(ab_rx.try_iter().collect::<Vec<usize>>(), vec![0, 1, 3]);
+        assert_eq!(c_rx.try_iter().collect::<Vec<usize>>(), vec![0, 10]);
+
+        // Acks still count every event
+        assert_eq!(acks_rx.try_iter().last(), Some(4));
+
+        // `c` can't be affected by `a` or `b`, so doesn't hear about them
+        let c = t.metrics().values().find(|n| n.name == "c" && n.role == NodeRole::Node).unwrap().clone();
+        assert_eq!((c.changed, c.unchanged), (1, 0));
+
+        // `b` hears about `a` but not `c`
+        let b = t.metrics().values().find(|n| n.name == "b" && n.role == NodeRole::Link).unwrap().clone();
+        assert_eq!((b.changed, b.unchanged), (1, 1));
+
+        // Each of the join's inputs is counted separately
+        let inputs = t.metrics().values()
+            .filter(|n| n.name == "lift2" && n.role != NodeRole::Node)
+            .map(|n| n.role)
+            .collect::<Vec<NodeRole>>();
+        assert_eq!(inputs, vec![NodeRole::JoinInput(0), NodeRole::JoinInput(1)]);
+    }
+
+    fn attach_joined_subgraph_with(executor: Executor) {
+        let (in_tx, in_rx) = channel();
+        let mut config: Config = Default::default();
+        config.executor = executor;
+
+        let mut built = None;
+        let mut handle = spawn_topology(config, |t| {
+            let input = t.listen(0usize, in_rx);
+            built = Some((input.clone(), t.output(input)));
+        });
+        let (input, input_rx) = built.unwrap();
+        assert_eq!(input_rx.recv().unwrap(), 0);
+
+        // Both sides of the join start at the same event, however many
+        // events are sent while attaching
+        let sender = thread::spawn(move || {
+            for i in 1..200 {
+                in_tx.send(i).unwrap();
+            }
+            in_tx
+        });
+
+        let mut out = None;
+        let attachment = handle.attach(&input, |t, input| {
+            out = Some(t.output(input.clone().lift2(input.lift(|i| { i * 2 }), |i, j| { (*i, *j) })));
+        }).unwrap();
+        let out_rx = out.unwrap();
+
+        let in_tx = sender.join().unwrap();
+        in_tx.send(200).unwrap();
+
+        for (i, j) in out_rx.iter() {
+            assert_eq!(i * 2, j);
+            if i == 200 { break }
+        }
+
+        handle.detach(attachment);
+        assert!(out_rx.recv().is_err());
+
+        // The rest of the topology carries on
+        assert_eq!(input_rx.iter().take(200).last(), Some(200));
+        in_tx.send(201).unwrap();
+        assert_eq!(input_rx.recv().unwrap(), 201);
+    }
+
+    #[test]
+    fn attach_joined_subgraph() {
+        attach_joined_subgraph_with(Executor::ThreadPerNode);
+    }
+
+    #[test]
+    fn attach_joined_subgraph_pool() {
+        attach_joined_subgraph_with(Executor::Pool(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "TopologyHandle::attach")]
+    fn attach_cant_read_other_branches() {
+        let (in_tx, in_rx) = channel();
+        let (_other_tx, other_rx) = channel();
+
+        let mut built = None;
+        let mut handle = spawn_topology(Default::default(), |t| {
+            let input = t.listen(0usize, in_rx);
+            let other = t.listen(0usize, other_rx);
+            built = Some((input.clone(), other, t.output(input)));
+        });
+        let (input, other, input_rx) = built.unwrap();
+
+        // The input's fork is running once it's handled an event
+        in_tx.send(1).unwrap();
+        assert_eq!(input_rx.iter().take(2).last(), Some(1));
+
+        handle.attach(&input, |t, input| {
+            t.output(input.lift2(other, |i, j| { *i + *j }));
+        });
+    }
+
+    #[test]
+    fn switch_keeps_unselected_branches_running() {
+        use std::collections::HashMap;
+
+        let (mode_tx, mode_rx) = channel();
+        let (in_tx, in_rx) = channel();
+        let mut out = None;
+
+        let _handle = spawn_topology(Default::default(), |t| {
+            let input = t.listen(0usize, in_rx);
+
+            let mut branches = HashMap::new();
+            branches.insert(0, t.add(input.clone().lift(|i| { i + 1 })));
+            branches.insert(1, t.add(input.fold(0, |sum, i| { sum + i })));
+
+            out = Some(t.output(t.listen(0, mode_rx).switch(branches)));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 1);
+
+        // The unselected fold keeps accumulating
+        for i in 1..11 {
+            in_tx.send(i).unwrap();
+            assert_eq!(out_rx.recv().unwrap(), i + 1);
+        }
+
+        mode_tx.send(1).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 55);
+
+        in_tx.send(11).unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 66);
+    }
+
+    #[test]
+    fn tumbling_skips_incomplete_batches() {
+        let (in_tx, in_rx) = channel();
+        let mut out = None;
+
+        let _handle = spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.listen(0usize, in_rx)
+                .tumbling(3)
+                .fold(0, |batches, _| { batches + 1 })));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 1);
+
+        for i in 1..9 {
+            in_tx.send(i).unwrap();
+        }
+
+        // Only completed batches reach the fold, the first including the
+        // initial value
+        assert_eq!(out_rx.recv().unwrap(), 2);
+        assert_eq!(out_rx.recv().unwrap(), 3);
+        assert_eq!(out_rx.recv().unwrap(), 4);
+        assert!(out_rx.recv_timeout(Duration::from_millis(50)).is_err());
+    }
+
+    #[test]
+    fn fps_when_threaded() {
+        let (gate_tx, gate_rx) = channel();
+        let mut out = None;
+
+        let _handle = spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.fps_when(100, t.listen(true, gate_rx))));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), Duration::from_secs(0));
+
+        for _ in 0..3 {
+            assert!(out_rx.recv().unwrap() > Duration::from_secs(0));
+        }
+
+        gate_tx.send(false).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        out_rx.try_iter().count();
+        assert!(out_rx.recv_timeout(Duration::from_millis(100)).is_err());
+
+        gate_tx.send(true).unwrap();
+        assert!(out_rx.recv().unwrap() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn delay_is_its_own_event() {
+        let clock = VirtualClock::new();
+        let mut config: Config = Default::default();
+        config.clock = Arc::new(clock.clone());
+
+        let (_tx, rx) = channel();
+        let mut out = None;
+        let mut t = TestTopology::new(config, |t| {
+            let input = t.listen(0usize, rx);
+            let delayed = input.clone().delay(t, Duration::from_secs(1));
+
+            out = Some(t.output(input.lift2(delayed, |i, d| { (*i, *d) })));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.try_recv().unwrap(), (0, 0));
+
+        t.push(0, 1usize);
+        assert_eq!(out_rx.try_recv().unwrap(), (1, 0));
+
+        clock.advance(Duration::from_secs(1));
+        while t.step() {}
+        assert_eq!(out_rx.try_iter().collect::<Vec<_>>(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn debounce_threaded() {
+        let (in_tx, in_rx) = channel();
+        let mut out = None;
+
+        let _handle = spawn_topology(Default::default(), |t| {
+            out = Some(t.output(t.listen(0usize, in_rx).debounce(t, Duration::from_millis(50))));
+        });
+        let out_rx = out.unwrap();
+        assert_eq!(out_rx.recv().unwrap(), 0);
+
+        for i in 1..4 {
+            in_tx.send(i).unwrap();
+        }
+
+        assert_eq!(out_rx.recv().unwrap(), 3);
+        assert!(out_rx.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn fuse_single_branch() {
+        fn thread_name() -> String {
+            thread::current().name().unwrap_or("").to_string()
+        }
+
+        let (single_tx, single_rx) = channel();
+        let (shared_tx, shared_rx) = channel();
+        let mut outputs = None;
+
+        spawn_topology(Default::default(), |t| {
+            let single = t.listen(0, single_rx)
+                .lift(|_| { thread_name() })
+                .named("single")
+                .add_to(t);
+
+            let shared = t.listen(0, shared_rx)
+                .lift(|_| { thread_name() })
+                .named("shared")
+                .add_to(t);
+
+            outputs = Some((
+                t.output(single.named("consumer")),
+                t.output(shared.clone()),
+                t.output(shared),
+            ));
+        });
+        let (single_rx, shared_a_rx, shared_b_rx) = outputs.unwrap();
+
+        // Initial values are computed while building
+        single_rx.recv().unwrap();
+        shared_a_rx.recv().unwrap();
+        shared_b_rx.recv().unwrap();
+
+        // The only branch runs the lift itself
+        single_tx.send(1).unwrap();
+        assert_eq!(single_rx.recv().unwrap(), "consumer");
+
+        // Shared branches are sent the lift's results by its fork
+        shared_tx.send(1).unwrap();
+        assert_eq!(shared_a_rx.recv().unwrap(), "shared");
+        assert_eq!(shared_b_rx.recv().unwrap(), "shared");
+    }
+
+    #[test]
+    fn backpressure_drops_newest() {
+        let (in_tx, in_rx) = channel();
+        let mut config: Config = Default::default();
+        config.backpressure = BackpressurePolicy::DropNewest;
+        config.buffer_size = 1;
+
+        let mut out = None;
+        let handle = spawn_topology(config, |t| {
+            out = Some(t.output(t.listen(0usize, in_rx).lift(|i| {
+                thread::sleep(Duration::from_millis(1));
+                i
+            })));
+        });
+        let out_rx = out.unwrap();
+
+        for i in 1..100 {
+            in_tx.send(i).unwrap();
+        }
+        drop(in_tx);
+
+        let seen = out_rx.iter().collect::<Vec<usize>>();
+        let listen = handle.metrics().values().find(|n| n.name == "listen" && n.role == NodeRole::Link).unwrap().clone();
+
+        assert!(listen.dropped > 0);
+        assert_eq!(listen.changed + listen.dropped, 99);
+        assert_eq!(seen.len(), listen.changed + 1);
+        assert!(seen.windows(2).all(|w| { w[0] < w[1] }));
+    }
+
+    #[test]
+    fn backpressure_keeps_joins_in_step() {
+        let (in_tx, in_rx) = channel();
+        let mut out = None;
+
+        let handle = spawn_topology(Default::default(), |t| {
+            let input = t.listen(0usize, in_rx).lift(|i| { i }).add_to(t);
+            let slow = input.clone()
+                .with_config(|c| { c.backpressure = BackpressurePolicy::Latest; })
+                .named("slow")
+                .lift(|i| { thread::sleep(Duration::from_millis(1)); i });
+
+            // Room for every event, so the fast side never holds up the fork
+            let fast = input.with_config(|c| { c.buffer_size = 100; });
+
+            out = Some(t.output(slow.lift2(fast, |s, i| { (*s, *i) })));
+        });
+        let out_rx = out.unwrap();
+
+        for i in 1..100 {
+            in_tx.send(i).unwrap();
+        }
+        drop(in_tx);
+
+        // Dropped values are seen as unchanged, so each pair is from the
+        // same event or the slow side is behind
+        let seen = out_rx.iter().collect::<Vec<(usize, usize)>>();
+        assert_eq!(seen.iter().map(|&(_, i)| i).collect::<Vec<usize>>(), (0..100).collect::<Vec<usize>>());
+        assert!(seen.iter().all(|&(s, i)| s <= i));
+        assert_eq!(seen.last(), Some(&(99, 99)));
+
+        let slow = handle.metrics().values().find(|n| n.name == "slow" && n.role == NodeRole::Link).unwrap().clone();
+        assert!(slow.dropped > 0);
+    }
+
+    #[test]
+    fn pool_mailboxes_are_bounded() {
+        let (in_tx, in_rx) = channel();
+        let (release_tx, release_rx) = channel();
+        let mut config: Config = Default::default();
+        config.executor = Executor::Pool(2);
+        config.buffer_size = 2;
+
+        let mut handle = spawn_topology(config, |t| {
+            t.add(t.listen(0usize, in_rx).lift(move |i| {
+                if i > 0 { release_rx.recv().unwrap(); }
+                i
+            }));
+        });
+
+        for i in 1..20 {
+            in_tx.send(i).unwrap();
+        }
+        thread::sleep(Duration::from_millis(100));
+
+        // The lift is blocked on its first value, so the rest are left unread
+        // once its mailbox is full
+        assert!(handle.metrics().values().all(|n| n.queued <= 2));
+
+        for _ in 1..20 {
+            release_tx.send(()).unwrap();
+        }
+        assert_eq!(handle.stop_and_join(Duration::from_secs(5)), Ok(()));
+    }
+
+    #[test]
+    fn with_config_per_branch() {
+        let (in_tx, in_rx) = channel();
+        let mut outputs = None;
+
+        let handle = spawn_topology(Default::default(), |t| {
+            let input = t.listen(0usize, in_rx).lift(|i| { i }).add_to(t);
+            let slow = input.clone()
+                .with_config(|c| { c.backpressure = BackpressurePolicy::DropNewest; })
+                .named("slow")
+                .lift(|i| { thread::sleep(Duration::from_millis(5)); i });
+
+            outputs = Some((t.output(input), t.output(slow)));
+        });
+        let (fast_rx, slow_rx) = outputs.unwrap();
+
+        for i in 1..50 {
+            in_tx.send(i).unwrap();
+        }
+        drop(in_tx);
+
+        assert_eq!(fast_rx.iter().collect::<Vec<usize>>(), (0..50).collect::<Vec<usize>>());
+        assert!(slow_rx.iter().count() < 50);
+
+        let metrics = handle.metrics();
+        let slow = metrics.values().find(|n| n.name == "slow" && n.role == NodeRole::Link).unwrap();
+        assert!(slow.dropped > 0);
+        assert!(metrics.values().filter(|n| n.name != "slow" || n.role != NodeRole::Link).all(|n| n.dropped == 0));
+    }
+
     #[test]
     fn map() {
         let (in_tx, in_rx) = sync_channel(0);
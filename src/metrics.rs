@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::*;
+use std::sync::atomic::*;
+use std::time::{Duration, Instant};
+
+use super::Event;
+
+/// A snapshot of the counters kept for one node of a topology
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeStats {
+    /// The node's name, or its kind if it hasn't been named
+    ///
+    /// Links and join inputs are named after the node they lead to, so
+    /// names are only unique together with `role`.
+    pub name: String,
+    /// What the counters are kept for
+    pub role: NodeRole,
+    /// The number of `Event::Changed` events handled by the node
+    pub changed: usize,
+    /// The number of `Event::Unchanged` events handled by the node
+    pub unchanged: usize,
+    /// The number of `Event::Exit` events handled by the node
+    pub exited: usize,
+    /// Time spent in the functions passed to the node
+    pub busy: Duration,
+    /// The number of events waiting to be handled by the node, for nodes
+    /// which receive events through a channel or queue
+    pub queued: usize,
+    /// The number of events dropped on their way to the node, see
+    /// `BackpressurePolicy`
+    pub dropped: usize,
+}
+
+/// What a set of counters is kept for
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodeRole {
+    /// A node, such as a `lift` or a `fold`
+    #[default]
+    Node,
+    /// The link carrying events to a node from another thread or worker
+    Link,
+    /// One of the inputs of a node joining several signals, such as `lift2`,
+    /// numbered from 0 in argument order
+    JoinInput(usize),
+}
+
+/// Collects the counters kept by the nodes of a topology
+///
+/// Each topology gets its own when its `Builder` is created; snapshots are
+/// exposed by `TopologyHandle::metrics`.
+///
+#[derive(Clone)]
+pub struct Metrics {
+    nodes: Arc<Mutex<Vec<NodeMetrics>>>,
+}
+
+impl Metrics {
+    /// Create an empty set of metrics
+    ///
+    pub fn new() -> Metrics {
+        Metrics {
+            nodes: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Start keeping counters for a node called `name`
+    ///
+    pub fn register(&self, name: &str, role: NodeRole) -> NodeMetrics {
+        let node = NodeMetrics {
+            inner: Arc::new(NodeCounters {
+                name: name.to_string(),
+                role,
+                changed: AtomicUsize::new(0),
+                unchanged: AtomicUsize::new(0),
+                exited: AtomicUsize::new(0),
+                busy_ns: AtomicU64::new(0),
+                queued: AtomicUsize::new(0),
+                dropped: AtomicUsize::new(0),
+            }),
+        };
+
+        self.nodes.lock().unwrap().push(node.clone());
+        node
+    }
+
+    /// The current value of every node's counters, keyed by node id
+    ///
+    /// Ids are given out in the order nodes are registered, which is the
+    /// order the topology's nodes are started in.
+    ///
+    pub fn snapshot(&self) -> BTreeMap<usize, NodeStats> {
+        self.nodes.lock().unwrap().iter().enumerate().map(|(id, node)| {
+            (id, node.stats())
+        }).collect()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics::new()
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "Metrics")
+    }
+}
+
+/// The counters kept for one node
+///
+#[derive(Clone)]
+pub struct NodeMetrics {
+    inner: Arc<NodeCounters>,
+}
+
+struct NodeCounters {
+    name: String,
+    role: NodeRole,
+    changed: AtomicUsize,
+    unchanged: AtomicUsize,
+    exited: AtomicUsize,
+    busy_ns: AtomicU64,
+    queued: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl NodeMetrics {
+    /// Count an event handled by the node
+    ///
+    pub fn record<A>(&self, event: &Event<A>) {
+        let counter = match *event {
+            Event::Changed(_) => &self.inner.changed,
+            Event::Unchanged => &self.inner.unchanged,
+            Event::Exit => &self.inner.exited,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Run `f`, adding the time it takes to the node's busy time
+    ///
+    pub fn time<F, R>(&self, f: F) -> R where
+        F: FnOnce() -> R,
+    {
+        let start = Instant::now();
+        let r = f();
+        let elapsed = start.elapsed();
+
+        self.inner.busy_ns.fetch_add(elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64, Ordering::Relaxed);
+        r
+    }
+
+    /// Record that an event is waiting to be handled by the node
+    ///
+    pub fn enqueued(&self) {
+        self.inner.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record that a waiting event has been taken by the node
+    ///
+    pub fn dequeued(&self) {
+        self.inner.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Record that an event was dropped before reaching the node
+    ///
+    pub fn dropped(&self) {
+        self.inner.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The current value of the node's counters
+    ///
+    pub fn stats(&self) -> NodeStats {
+        NodeStats {
+            name: self.inner.name.clone(),
+            role: self.inner.role,
+            changed: self.inner.changed.load(Ordering::Relaxed),
+            unchanged: self.inner.unchanged.load(Ordering::Relaxed),
+            exited: self.inner.exited.load(Ordering::Relaxed),
+            busy: Duration::from_nanos(self.inner.busy_ns.load(Ordering::Relaxed)),
+            queued: self.inner.queued.load(Ordering::Relaxed),
+            dropped: self.inner.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
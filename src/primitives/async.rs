@@ -1,17 +1,47 @@
+use std::sync::Arc;
 use std::sync::mpsc::*;
+use std::time::Instant;
 
-use super::super::{Event, Signal, SignalType, Push, Run};
+use super::super::{Event, Signal, SignalType, Push, Run, Node, NodeMetrics, NodeRole};
+use clock::Clock;
+
+/// The channel an `Async` sends its parent's values to
+///
+/// Bounded channels block the async subgraph until its values have been
+/// received; executors which can't let a node block use unbounded channels.
+/// Timed channels are unbounded, and send each value with the time it was
+/// sent according to the clock.
+///
+pub enum AsyncSender<A> {
+    Bounded(SyncSender<A>),
+    Unbounded(Sender<A>),
+    Timed(Sender<(Instant, A)>, Arc<Clock>),
+}
+
+impl<A> AsyncSender<A> {
+    fn send(&self, a: A) -> Result<(), SendError<A>> {
+        match *self {
+            AsyncSender::Bounded(ref tx) => tx.send(a),
+            AsyncSender::Unbounded(ref tx) => tx.send(a),
+            AsyncSender::Timed(ref tx, ref clock) => {
+                tx.send((clock.now(), a)).map_err(|SendError((_, a))| SendError(a))
+            },
+        }
+    }
+}
 
 pub struct Async<A> {
     parent: Box<Signal<A>>,
-    tx: SyncSender<A>,
+    tx: AsyncSender<A>,
+    node: Node,
 }
 
 impl<A> Async<A> {
-    pub fn new(parent: Box<Signal<A>>, tx: SyncSender<A>) -> Async<A> {
+    pub fn new(parent: Box<Signal<A>>, tx: AsyncSender<A>, node: Node) -> Async<A> {
         Async {
             parent: parent,
             tx: tx,
+            node,
         }
     }
 }
@@ -20,43 +50,61 @@ impl<A> Run for Async<A> where
     A: 'static + Send + Clone
 {
     fn run(self: Box<Self>) {
-        debug!("Async::run");
-
         let inner = *self;
-        let Async { parent, tx } = inner;
+        let Async { parent, tx, node } = inner;
+        let config = parent.config();
+        let name = config.node_name(node.kind).to_string();
+        let metrics = config.metrics().register(&name, NodeRole::Node);
+
+        debug!("[{}] Async::run", name);
 
         match parent.initial() {
             SignalType::Constant(_) => return,
             SignalType::Dynamic(_) => {
-                parent.push_to(Some(Box::new(AsyncPusher {tx: tx})));
+                parent.push_to(Some(Box::new(AsyncPusher {name, metrics, tx})));
             },
         }
     }
+
+    fn name(&self) -> Option<String> {
+        self.parent.config().name()
+    }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn fuse(self: Box<Self>) -> Option<Box<Run>> {
+        Some(self)
+    }
 }
 
 struct AsyncPusher<A> {
-    tx: SyncSender<A>,
+    name: String,
+    metrics: NodeMetrics,
+    tx: AsyncSender<A>,
 }
 
 impl<A> Push<A> for AsyncPusher<A> where
     A: 'static + Clone + Send,
 {
     fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
 
         match event {
             Event::Changed(a) => {
-                debug!("Async handling Event Changed - pushing to channel");
+                debug!("[{}] Async handling Event Changed - pushing to channel", self.name);
                 match self.tx.send(a) {
                     // We can't really terminate a child process, so just ignore errors...
                     _ => {},
                 }
             },
             Event::Unchanged => {
-                debug!("Async handling Event Unchanged - doing nothing");
+                debug!("[{}] Async handling Event Unchanged - doing nothing", self.name);
                 // No change, so no point in pushing...
             },
             Event::Exit => {
-                debug!("Async handling Event Exit");
+                debug!("[{}] Async handling Event Exit", self.name);
                 // Exit should be propagated to all top-level inputs anyway, so
                 // nothing to do here...
             }
@@ -1,23 +1,24 @@
-use std::sync::mpsc::*;
-
-use super::super::{Event, Signal, SignalExt, SignalType, Push, Config};
+use super::super::{Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::link::LinkRx;
 
 pub struct Channel<A> where
     A: 'static + Send + Clone,
 {
     config: Config,
-    source_rx: Receiver<Event<A>>,
+    source_rx: LinkRx<A>,
     initial: A,
+    node: Node,
 }
 
 impl<A> Channel<A> where
     A: 'static + Send + Clone,
 {
-    pub fn new(config: Config, source_rx: Receiver<Event<A>>, initial: A) -> Channel<A> {
+    pub fn new(config: Config, source_rx: LinkRx<A>, initial: A, node: Node) -> Channel<A> {
         Channel {
             config: config,
             source_rx: source_rx,
             initial: initial,
+            node,
         }
     }
 }
@@ -33,41 +34,20 @@ impl<A> Signal<A> for Channel<A> where
         SignalType::Dynamic(self.initial.clone())
     }
 
-    fn push_to(self: Box<Self>, target: Option<Box<Push<A>>>) {
-        match target {
-            Some(mut t) => {
-                debug!("SETUP: Sending to Some");
-                loop {
-                    match self.source_rx.recv() {
-                        Err(e) => {
-                            info!("RUN: Channel source_rx received Err {}, exiting", e);
-                            t.push(Event::Exit);
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
 
-                            return
-                        },
-                        Ok(a) => {
-                            info!("RUN: Channel source_rx received data, pushing");
-                            t.push(a);
-                        },
-                    }
-                }
-            }
-            None => {
-                debug!("SETUP: Sending to None");
-                // Just ensuring the channel is drained so we don't get memory leaks
-                loop {
-                    match self.source_rx.recv() {
-                        Err(e) => {
-                            info!("RUN: Channel source_rx received Err {} with no target, exiting", e);
-                            return
-                        },
-                        _ => {
-                            info!("RUN: source_rx received data, but no target");
-                        },
-                    }
-                }
-            },
-        }
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        debug!("SETUP: [{}] Channel pushing from source", self.config.node_name(self.node.kind));
+        let inner = *self;
+        inner.source_rx.push_to(target);
     }
 }
 impl<A> SignalExt<A> for Channel<A> where A: 'static + Send + Clone {}
+
+impl<A> Configurable for Channel<A> where A: 'static + Send + Clone {
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
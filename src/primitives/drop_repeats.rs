@@ -0,0 +1,110 @@
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole};
+
+/// The result of a `drop_repeats` operation
+///
+pub struct DropRepeatsSignal<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    config: Config,
+    parent: Box<Signal<A>>,
+}
+
+impl<A> DropRepeatsSignal<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    pub fn new(config: Config, parent: Box<Signal<A>>) -> Self {
+        DropRepeatsSignal {
+            config,
+            parent,
+        }
+    }
+}
+
+impl<A> Signal<A> for DropRepeatsSignal<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<A> {
+        self.parent.initial()
+    }
+
+    fn describe(&self) -> Node {
+        let parent = self.parent.describe();
+        Node::new("drop_repeats", self.config.name(), parent.constant, vec![parent])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        let inner = *self;
+        let DropRepeatsSignal { config, parent } = inner;
+        let last = parent.initial().unwrap();
+
+        parent.push_to(
+            Some(
+                Box::new(
+                    DropRepeatsPusher {
+                        name: config.node_name("drop_repeats").to_string(),
+                        metrics: config.metrics().register(config.node_name("drop_repeats"), NodeRole::Node),
+                        child: target,
+                        last,
+                    }
+                )
+            )
+        );
+    }
+}
+impl<A> SignalExt<A> for DropRepeatsSignal<A> where
+    A: 'static + Send + Clone + PartialEq,
+{}
+
+impl<A> Configurable for DropRepeatsSignal<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
+struct DropRepeatsPusher<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    name: String,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<A> + Send>>,
+    last: A,
+}
+
+impl<A> Push<A> for DropRepeatsPusher<A> where
+    A: 'static + Send + Clone + PartialEq,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
+        let out = match event {
+            Event::Changed(a) => {
+                if a == self.last {
+                    info!("RUN: [{}] DropRepeatsPusher handling Event::Changed, repeated", self.name);
+                    Event::Unchanged
+                } else {
+                    info!("RUN: [{}] DropRepeatsPusher handling Event::Changed", self.name);
+                    self.last = a.clone();
+                    Event::Changed(a)
+                }
+            },
+            Event::Unchanged => {
+                info!("RUN: [{}] DropRepeatsPusher handling Event::Unchanged", self.name);
+                Event::Unchanged
+            },
+            Event::Exit => {
+                info!("RUN: [{}] DropRepeatsPusher handling Event::Exit", self.name);
+                Event::Exit
+            },
+        };
+
+        if let Some(ref mut t) = self.child {
+            t.push(out);
+        }
+    }
+}
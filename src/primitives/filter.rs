@@ -0,0 +1,139 @@
+use std::marker::*;
+
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole};
+
+/// The result of a `filter_map` operation
+///
+pub struct FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    config: Config,
+    parent: Box<Signal<A>>,
+    f: F,
+    initial: SignalType<B>,
+}
+
+impl<F, A, B> FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    pub fn new(config: Config, parent: Box<Signal<A>>, default: B, f: F) -> Self {
+        let initial = match parent.initial() {
+            SignalType::Constant(a) => SignalType::Constant(f(a).unwrap_or(default)),
+            SignalType::Dynamic(a) => SignalType::Dynamic(f(a).unwrap_or(default)),
+        };
+
+        FilterMapSignal {
+            config,
+            parent,
+            f,
+            initial,
+        }
+    }
+}
+
+impl<F, A, B> Signal<B> for FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<B> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("filter_map", self.config.name(), self.initial.is_constant(), vec![self.parent.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
+        let inner = *self;
+        let FilterMapSignal { config, parent, f, initial: _ } = inner;
+
+        parent.push_to(
+            Some(
+                Box::new(
+                    FilterMapPusher {
+                        name: config.node_name("filter_map").to_string(),
+                        metrics: config.metrics().register(config.node_name("filter_map"), NodeRole::Node),
+                        config,
+                        child: target,
+                        f,
+                        marker: PhantomData,
+                    }
+                )
+            )
+        );
+    }
+}
+impl<F, A, B> SignalExt<B> for FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{}
+
+impl<F, A, B> Configurable for FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
+struct FilterMapPusher<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<B> + Send>>,
+    f: F,
+    marker: PhantomData<A>,
+}
+
+impl<F, A, B> Push<A> for FilterMapPusher<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
+        let out = match event {
+            Event::Changed(a) => {
+                match self.metrics.time(|| self.config.supervisor().guard(&self.name, || (self.f)(a))).and_then(|b| b) {
+                    Some(b) => {
+                        info!("RUN: [{}] FilterMapPusher handling Event::Changed, accepted", self.name);
+                        Event::Changed(b)
+                    },
+                    None => {
+                        info!("RUN: [{}] FilterMapPusher handling Event::Changed, rejected", self.name);
+                        Event::Unchanged
+                    },
+                }
+            },
+            Event::Unchanged => {
+                info!("RUN: [{}] FilterMapPusher handling Event::Unchanged", self.name);
+                Event::Unchanged
+            },
+            Event::Exit => {
+                info!("RUN: [{}] FilterMapPusher handling Event::Exit", self.name);
+                Event::Exit
+            },
+        };
+
+        if let Some(ref mut t) = self.child {
+            t.push(out);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::marker::*;
 
-use super::super::{Event, Signal, SignalExt, SignalType, Push, Config};
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole, PanicPolicy};
 
 /// The result of a `fold` operation
 ///
@@ -54,9 +54,13 @@ impl<F, A, B> Signal<B> for FoldSignal<F, A, B> where
         self.state.clone()
     }
 
-    fn push_to(self: Box<Self>, target: Option<Box<Push<B>>>) {
+    fn describe(&self) -> Node {
+        Node::new("fold", self.config.name(), self.state.is_constant(), vec![self.parent.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
         let inner = *self;
-        let FoldSignal {config: _, parent, f, state} = inner;
+        let FoldSignal {config, parent, f, state} = inner;
 
         let s = match state {
             SignalType::Constant(s) => s,
@@ -69,6 +73,9 @@ impl<F, A, B> Signal<B> for FoldSignal<F, A, B> where
                     Some(
                         Box::new(
                             FoldPusher {
+                                name: config.node_name("fold").to_string(),
+                                metrics: config.metrics().register(config.node_name("fold"), NodeRole::Node),
+                                config,
                                 child: Some(t),
                                 f: f,
                                 state: s,
@@ -83,6 +90,9 @@ impl<F, A, B> Signal<B> for FoldSignal<F, A, B> where
                     Some(
                         Box::new(
                             FoldPusher {
+                                name: config.node_name("fold").to_string(),
+                                metrics: config.metrics().register(config.node_name("fold"), NodeRole::Node),
+                                config,
                                 child: None,
                                 f: f,
                                 state: s,
@@ -101,12 +111,25 @@ impl<F, A, B> SignalExt<B> for FoldSignal<F, A, B> where
     B: 'static + Send + Clone,
 {}
 
+impl<F, A, B> Configurable for FoldSignal<F, A, B> where
+    F: 'static + Send + Fn(B, A) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
 struct FoldPusher<F, A, B> where
     F: 'static + Send + Fn(B, A) -> B,
     A: 'static + Send + Clone,
     B: 'static + Send + Clone,
 {
-    child: Option<Box<Push<B>>>,
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<B> + Send>>,
     f: F,
     state: B,
     marker: PhantomData<A>,
@@ -118,18 +141,31 @@ impl<F, A, B> Push<A> for FoldPusher<F, A, B> where
     B: 'static + Send + Clone,
 {
     fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
         let out = match event {
             Event::Changed(a) => { 
-                debug!("FoldPusher handling Event::Changed");
-                self.state = (self.f)(self.state.clone(), a);
-                Event::Changed(self.state.clone())
+                debug!("[{}] FoldPusher handling Event::Changed", self.name);
+                let state = self.state.clone();
+                match self.metrics.time(|| self.config.supervisor().guard(&self.name, || (self.f)(state, a))) {
+                    Some(s) => {
+                        self.state = s;
+                        Event::Changed(self.state.clone())
+                    },
+                    None => {
+                        match self.config.panic_policy {
+                            PanicPolicy::Restart => Event::Changed(self.state.clone()),
+                            _ => Event::Unchanged,
+                        }
+                    },
+                }
             },
             Event::Unchanged => {
-                debug!("FoldPusher handling Event::Unchanged");
+                debug!("[{}] FoldPusher handling Event::Unchanged", self.name);
                 Event::Unchanged
             },
             Event::Exit => {
-                debug!("FoldPusher handling Event::Exit");
+                debug!("[{}] FoldPusher handling Event::Exit", self.name);
                 Event::Exit
             },
         };
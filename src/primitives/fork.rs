@@ -1,7 +1,7 @@
 use std::sync::*;
-use std::sync::mpsc::*;
 
-use super::super::{Event, Signal, SignalExt, SignalType, Push, Run, Config};
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Run, Config, Overrides, Node, NodeMetrics, NodeRole};
+use super::link::{link, LinkTx, LinkRx, LinkEnds};
 
 // A Fork is created internally when Builder#add is called.  The purpose of Fork is
 // to distribute incoming data to some number of child Branch instances.
@@ -14,20 +14,30 @@ use super::super::{Event, Signal, SignalExt, SignalType, Push, Run, Config};
 // for all the nodes upstream of the Fork. Forks run in their data source's 
 // thread.
 //
+// When a Fork has a single Branch it's fused with it before the topology is
+// run: rather than sending data to the Branch over a link, the Fork hands its
+// parent to the Branch, so the parent's transformations run in the thread
+// consuming the Branch.  Forks with Branches which are still held elsewhere,
+// which may be attached to once the topology is running, aren't fused.
+//
 pub struct Fork<A> where
     A: 'static + Send,
 {
     parent: Box<Signal<A>>,
-    sink_txs: Arc<Mutex<Vec<SyncSender<Event<A>>>>>,
+    sink_txs: Arc<Mutex<Sinks<A>>>,
+    fused: Arc<Mutex<Option<Box<Signal<A>>>>>,
+    node: Node,
 }
 
 impl<A> Fork<A> where
     A: 'static + Clone + Send,
 {
-    pub fn new(parent: Box<Signal<A>>, sink_txs: Arc<Mutex<Vec<SyncSender<Event<A>>>>>) -> Fork<A> {
+    pub fn new(parent: Box<Signal<A>>, sink_txs: Arc<Mutex<Sinks<A>>>, fused: Arc<Mutex<Option<Box<Signal<A>>>>>, node: Node) -> Fork<A> {
         Fork {
             parent: parent,
             sink_txs: sink_txs,
+            fused,
+            node,
         }
     }
 }
@@ -39,28 +49,36 @@ impl<A> Run for Fork<A> where
     A: 'static + Clone + Send,
 {
     fn run(self: Box<Self>) {
+        let name = self.parent.config().node_name("fork").to_string();
+        self.sink_txs.lock().unwrap().running = true;
+
         match self.parent.initial() {
             SignalType::Constant(_) => return,
             SignalType::Dynamic(_) => {
-                let has_branches = !self.sink_txs.lock().unwrap().is_empty();
+                // Branches held outside the topology may be attached to
+                let has_branches = !self.sink_txs.lock().unwrap().txs.is_empty() ||
+                    Arc::strong_count(&self.sink_txs) > 1;
 
                 if has_branches {
-                    debug!("Fork::run with branches");
+                    debug!("[{}] Fork::run with branches", name);
 
                     let inner = *self;
-                    let Fork { parent, sink_txs } = inner;
+                    let Fork { parent, sink_txs, fused: _, node: _ } = inner;
+                    let metrics = parent.config().metrics().register(&name, NodeRole::Node);
 
                     parent.push_to(
                         Some(
                             Box::new(
                                 ForkPusher {
+                                    name,
+                                    metrics,
                                     sink_txs: sink_txs,
                                 }
                                 )
                             )
                         )
                 } else {
-                    debug!("Fork::run without branches");
+                    debug!("[{}] Fork::run without branches", name);
 
                     self.parent.push_to(None);
                 }
@@ -68,31 +86,104 @@ impl<A> Run for Fork<A> where
         }
                 
     }
+
+    fn name(&self) -> Option<String> {
+        self.parent.config().name()
+    }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn fuse(self: Box<Self>) -> Option<Box<Run>> {
+        let fusable = match self.parent.initial() {
+            SignalType::Constant(_) => false,
+            SignalType::Dynamic(_) => {
+                // Lossy links can't be done away with, they decide which
+                // events the Branch sees.  Neither can the Fork if anything
+                // other than the consuming Branch could attach to it
+                let sinks = self.sink_txs.lock().unwrap();
+                sinks.txs.len() == 1 && !sinks.txs[0].is_lossy() &&
+                    Arc::strong_count(&self.sink_txs) == 2
+            },
+        };
+
+        if !fusable { return Some(self) }
+
+        let inner = *self;
+        let Fork { parent, sink_txs, fused, node: _ } = inner;
+        debug!("[{}] Fork::fuse with its only branch", parent.config().node_name("fork"));
+
+        sink_txs.lock().unwrap().txs.clear();
+        *fused.lock().unwrap() = Some(parent);
+        None
+    }
+}
+
+/// The links a Fork sends its events to, along with what it has sent so far
+///
+/// Sinks are shared between a Fork and its Branches, and locked while the
+/// Fork sends each event, so Branches attached to a running topology start
+/// from an event boundary.
+///
+pub struct Sinks<A> {
+    txs: Vec<LinkTx<A>>,
+    // The last changed value sent, if any
+    latest: Option<A>,
+    exited: bool,
+    // Set once the Fork runs, after which Branches can only be tapped
+    running: bool,
+}
+
+impl<A> Sinks<A> {
+    pub fn new() -> Sinks<A> {
+        Sinks {
+            txs: Vec::new(),
+            latest: None,
+            exited: false,
+            running: false,
+        }
+    }
+}
+
+impl<A> Default for Sinks<A> {
+    fn default() -> Sinks<A> {
+        Sinks::new()
+    }
 }
 
 struct ForkPusher<A> {
-    sink_txs: Arc<Mutex<Vec<SyncSender<Event<A>>>>>,
+    name: String,
+    metrics: NodeMetrics,
+    sink_txs: Arc<Mutex<Sinks<A>>>,
 }
 
 impl<A> Push<A> for ForkPusher<A> where
     A: 'static + Clone + Send,
 {
     fn push(&mut self, event: Event<A>) {
-        debug!("ForkPusher handling Event");
+        debug!("[{}] ForkPusher handling Event", self.name);
+        self.metrics.record(&event);
 
-        for sink_tx in self.sink_txs.lock().unwrap().iter() {
-            match sink_tx.send(event.clone()) {
-                // We can't really terminate a child process, so just ignore errors...
-                _ => {},
-            }
+        let mut sinks = self.sink_txs.lock().unwrap();
+        match event {
+            Event::Changed(ref a) => sinks.latest = Some(a.clone()),
+            Event::Unchanged => {},
+            Event::Exit => sinks.exited = true,
         }
+
+        // Links whose receiving end has gone, such as detached Branches, are
+        // dropped
+        sinks.txs.retain(|sink_tx| sink_tx.send(event.clone()));
     }
 }
 
 /// A data source of type `A` which can be used as input more than once
 ///
 /// This operation is equivalent to a "let" binding, or variable assignment.
-/// Branch implements `Clone`, and each clone runs in its own thread.
+/// Branch implements `Clone`, and each clone runs in its own thread when using
+/// `Executor::ThreadPerNode`, unless it's an input of a join such as `lift2`,
+/// which receives from all of its inputs in its own thread.
 ///
 /// Branches are returned when `add` is called on a `Builder`
 ///
@@ -100,20 +191,118 @@ pub struct Branch<A> where
     A: 'static + Send,
 {
     config: Config,
-    fork_txs: Arc<Mutex<Vec<SyncSender<Event<A>>>>>,
-    source_rx: Option<Receiver<Event<A>>>,
+    fork_txs: Arc<Mutex<Sinks<A>>>,
+    fused: Arc<Mutex<Option<Box<Signal<A>>>>>,
+    source_rx: Option<LinkRx<A>>,
     initial: SignalType<A>,
+    node: Node,
 }
 
 impl<A> Branch<A> where
     A: 'static + Send,
 {
-    pub fn new(config: Config, fork_txs: Arc<Mutex<Vec<SyncSender<Event<A>>>>>, source_rx: Option<Receiver<Event<A>>>, initial: SignalType<A>) -> Branch<A> {
+    pub fn new(config: Config, fork_txs: Arc<Mutex<Sinks<A>>>, fused: Arc<Mutex<Option<Box<Signal<A>>>>>, source_rx: Option<LinkRx<A>>, initial: SignalType<A>, node: Node) -> Branch<A> {
         Branch {
             config: config,
             fork_txs: fork_txs,
+            fused,
             source_rx: source_rx,
             initial: initial,
+            node,
+        }
+    }
+}
+
+impl<A> Branch<A> where
+    A: 'static + Send + Clone,
+{
+    /// Override the builder's config for the link feeding this consumer
+    ///
+    /// Each clone of a `Branch` gets its own link from the fork when it's
+    /// consumed, so overrides only apply to the consumer built from this
+    /// clone; signals built from that consumer start out with the builder's
+    /// config again.  See `Overrides` for the settings which can be changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Mutex;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = channel();
+    /// let (release_tx, release_rx) = channel::<()>();
+    /// let release_rx = Mutex::new(release_rx);
+    /// let mut outputs = None;
+    ///
+    /// spawn_topology(Default::default(), |t| {
+    ///     let input = t.listen(0usize, in_rx);
+    ///
+    ///     // Queues up to 64 values while the lift waits to be released
+    ///     let slow = input.clone()
+    ///         .with_config(|c| { c.buffer_size = 64; })
+    ///         .lift(move |i| {
+    ///             if i > 0 { release_rx.lock().unwrap().recv().unwrap(); }
+    ///             i
+    ///         });
+    ///
+    ///     outputs = Some((t.output(input), t.output(slow)));
+    /// });
+    /// let (fast_rx, slow_rx) = outputs.unwrap();
+    ///
+    /// for i in 1..11 { in_tx.send(i).unwrap(); }
+    ///
+    /// // The slow consumer's buffer keeps it from holding up the input
+    /// assert_eq!(fast_rx.iter().take(11).last(), Some(10));
+    ///
+    /// for _ in 1..11 { release_tx.send(()).unwrap(); }
+    /// assert_eq!(slow_rx.iter().take(11).last(), Some(10));
+    /// ```
+    ///
+    pub fn with_config<F>(mut self, f: F) -> Self where
+        F: FnOnce(&mut Overrides),
+    {
+        let mut overrides = self.config.overrides();
+        f(&mut overrides);
+        self.config.set_overrides(overrides);
+        self
+    }
+
+    /// Start sending the Fork's events to a new link, from the next event it
+    /// handles
+    ///
+    /// Returns the Fork's latest value, and the link's ends if the Fork is
+    /// still running.  Constant Branches and Forks which have exited only
+    /// return their value.
+    ///
+    pub fn tap(&self, config: &Config, kind: &str) -> (A, Option<LinkEnds<A>>) {
+        let mut sinks = self.fork_txs.lock().unwrap();
+
+        let latest = match sinks.latest {
+            Some(ref a) => a.clone(),
+            None => self.initial.clone().unwrap(),
+        };
+
+        if self.initial.is_constant() || sinks.exited {
+            return (latest, None)
+        }
+
+        let (tx, rx) = link(config, kind);
+        sinks.txs.push(tx.clone());
+        (latest, Some((tx, rx)))
+    }
+
+    /// Send `Event::Exit` to a link created by `tap`, between two of the
+    /// Fork's events
+    ///
+    /// The Fork stops sending to the link once its receiving end has gone.
+    ///
+    pub fn untap(&self, tx: &LinkTx<A>) {
+        let sinks = self.fork_txs.lock().unwrap();
+
+        if !sinks.exited {
+            tx.send(Event::Exit);
         }
     }
 }
@@ -132,43 +321,38 @@ impl<A> Signal<A> for Branch<A> where
         self.initial.clone()
     }
 
-    fn push_to(self: Box<Self>, target: Option<Box<Push<A>>>) {
-        match (target, self.source_rx) {
-            (Some(mut t), Some(rx)) => {
-                debug!("Branch::push_to with target");
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
 
-                loop {
-                    match rx.recv() {
-                        Ok(event) => t.push(event),
-                        Err(_) => return,
-                    }
-                }
-            },
-            (None, Some(rx)) => {
-                debug!("Branch::push_to with empty target");
-
-                // Just ensuring the channel is drained so we don't get memory leaks
-                loop {
-                    match rx.recv() {
-                        Err(_) => return,
-                        _ => {},
-                    }
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        match self.source_rx {
+            Some(rx) => {
+                let fused = self.fused.lock().unwrap().take();
+                if let Some(parent) = fused {
+                    debug!("[{}] Branch::push_to fused with fork", self.config.node_name("branch"));
+                    return parent.push_to(rx.bypass(target))
                 }
-            },
-            (Some(_), None) => {
-                debug!("Branch::push_to with no source")
-            },
 
-            (None, None) => {
-                debug!("Branch::push_to with neither target nor source")
+                debug!("[{}] Branch::push_to with source", self.config.node_name("branch"));
+                rx.push_to(target);
+            },
+            None => {
+                debug!("[{}] Branch::push_to with no source", self.config.node_name("branch"))
             },
-
         }
     }
 
     fn init(&mut self) {
-        let (tx, rx) = sync_channel(self.config.buffer_size.clone());
-        self.fork_txs.lock().unwrap().push(tx);
+        // Checked before locking for the link, so the Fork's lock isn't
+        // poisoned by the panic
+        let running = self.fork_txs.lock().unwrap().running;
+        if running {
+            panic!("branches of a running topology can only be read with TopologyHandle::attach");
+        }
+
+        let (tx, rx) = link(&self.config, "branch");
+        self.fork_txs.lock().unwrap().txs.push(tx);
         self.source_rx = Some(rx);
     }
 }
@@ -176,6 +360,14 @@ impl<A> SignalExt<A> for Branch<A> where
     A: 'static + Send + Clone,
 {}
 
+impl<A> Configurable for Branch<A> where
+    A: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
 impl<A> Clone for Branch<A> where
     A: 'static + Send + Clone,
 {
@@ -183,8 +375,10 @@ impl<A> Clone for Branch<A> where
         Branch { 
             config: self.config(),
             fork_txs: self.fork_txs.clone(), 
+            fused: self.fused.clone(),
             source_rx: None, 
             initial: self.initial.clone(), 
+            node: self.node.clone(),
         }
     }
 }
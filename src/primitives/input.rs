@@ -1,29 +1,256 @@
 use rand;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::mem;
 use std::sync::*;
+use std::sync::atomic::*;
 use std::sync::mpsc::*;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use super::super::Event;
+use super::super::{Event, Run, Node};
+use clock::Clock;
+use executor::Scheduler;
+use graph;
+use super::link::LinkTx;
+use topology::spawn;
+
+/// How often inputs waiting for data or on a clock check whether the
+/// topology has been stopped
+///
+const POLL_INTERVAL_MS: u64 = 10;
+
+// How long a polled input waits for `next`, checking the clock at least every
+// poll interval as it may not be the system's
+fn until(now: Instant, next: Instant) -> Duration {
+    let poll = Duration::from_millis(POLL_INTERVAL_MS);
+    if next > now && next - now < poll { next - now } else { poll }
+}
 
 pub trait NoOp: Send {
     fn send_no_change(&mut self) -> bool;
     fn send_exit(&self);
 }
 
+/// The result of polling an input
+///
+pub enum Poll {
+    /// An event was dispatched
+    Ready,
+    /// No data was waiting.  The input should be polled again within the
+    /// given duration if there is one, otherwise once the scheduler is woken
+    Idle(Option<Duration>),
+    /// The input won't produce any more events
+    Done,
+}
+
 pub trait RunInput: Send {
-    fn run(mut self: Box<Self>, usize, Arc<Mutex<Vec<Box<NoOp>>>>);
+    // Dispatches data as it arrives, blocking the calling thread until the
+    // topology exits
+    fn run(self: Box<Self>, idx: usize, txs: Arc<Dispatch>, stopped: Arc<AtomicBool>);
+
+    // Dispatches data which has already arrived, without blocking
+    fn poll(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> Poll;
+
+    // Dispatches data as if it had arrived, returns false if the topology has
+    // exited.  Panics if the input doesn't receive data of the given type
+    fn push(&mut self, idx: usize, a: Box<Any + Send>, txs: &Dispatch, stopped: &AtomicBool) -> bool;
+
     fn boxed_no_op(&self) -> Box<NoOp>;
+
+    // Arranges for `scheduler` to be woken when data arrives, for inputs
+    // polled by the workers of a scheduler.  Not needed for inputs whose
+    // data comes from the topology itself, as workers poll the inputs again
+    // after running a job
+    fn wake_with(&mut self, _: &Scheduler) {}
+
+    // Describes the input, which must be a shared node
+    fn describe(&self) -> Node;
+
+    // True if the input produces a value for every event, whichever input it
+    // came from
+    fn on_every_event(&self) -> bool {
+        false
+    }
+}
+
+/// Where the events dispatched by each input are sent
+///
+/// Inputs receive `Event::Unchanged` when another input changes so that
+/// joins, which wait for an event from each of their parents, stay in step.
+/// That's only needed for inputs with a runner in common, so inputs are
+/// grouped by the runners they reach and events only go to the dispatching
+/// input's group.  Inputs which produce a value for every event, such as
+/// `Builder::ack_value`, are sent every event along with their group.
+///
+/// Each group has a lock of its own, so inputs in different groups can
+/// dispatch at the same time.
+///
+pub struct Dispatch {
+    groups: Vec<Mutex<Group>>,
+    // The groups each input's events are sent to, in ascending order so
+    // that they're always locked in the same order
+    routes: Vec<Vec<usize>>,
+    // The number of groups which have been sent `Event::Exit`
+    exited: AtomicUsize,
+}
+
+// The inputs of one group, with their index in the topology
+struct Group {
+    no_ops: Vec<(usize, Box<NoOp>)>,
+    exited: bool,
+}
+
+impl Group {
+    // Sends `Event::Exit` to the group's inputs, once
+    fn exit(&mut self, exited: &AtomicUsize) {
+        if self.exited { return }
+        self.exited = true;
+
+        for (_, no_op_tx) in self.no_ops.iter() {
+            no_op_tx.send_exit();
+        }
+        exited.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl Dispatch {
+    /// Work out where the events from `inputs` need to go for `runners`
+    ///
+    pub fn new(inputs: &[Box<RunInput>], runners: &[Box<Run>]) -> Dispatch {
+        let nodes = inputs.iter().map(|i| i.describe()).collect::<Vec<Node>>();
+        let roots = runners.iter().map(|r| r.describe()).collect::<Vec<Node>>();
+        let components = graph::components(&nodes, &roots);
+
+        // Number the groups from 0, in the order of their first input
+        let mut ids = Vec::new();
+        let groups = components.iter().map(|c| {
+            match ids.iter().position(|id| id == c) {
+                Some(g) => g,
+                None => {
+                    ids.push(*c);
+                    ids.len() - 1
+                },
+            }
+        }).collect::<Vec<usize>>();
+
+        // Groups which are sent every event
+        let every = inputs.iter().zip(groups.iter())
+            .filter(|&(input, _)| input.on_every_event())
+            .map(|(_, group)| *group)
+            .collect::<Vec<usize>>();
+
+        let routes = groups.iter().map(|group| {
+            (0..ids.len())
+                .filter(|g| g == group || every.contains(g))
+                .collect()
+        }).collect();
+
+        let mut members = (0..ids.len()).map(|_| Group { no_ops: Vec::new(), exited: false }).collect::<Vec<Group>>();
+        for (i, (input, group)) in inputs.iter().zip(groups.iter()).enumerate() {
+            members[*group].no_ops.push((i, input.boxed_no_op()));
+        }
+
+        Dispatch {
+            groups: members.into_iter().map(Mutex::new).collect(),
+            routes,
+            exited: AtomicUsize::new(0),
+        }
+    }
+
+    /// Send `Event::Exit` to every input, unless the topology has already
+    /// been stopped
+    ///
+    /// This never waits for an input: groups which are busy dispatching an
+    /// event are sent `Event::Exit` by the dispatching input once it has
+    /// released them.
+    ///
+    pub fn stop(&self, stopped: &AtomicBool) {
+        if stopped.swap(true, Ordering::SeqCst) { return }
+
+        for group in self.groups.iter() {
+            match group.try_lock() {
+                Ok(mut group) => group.exit(&self.exited),
+                Err(TryLockError::Poisoned(e)) => e.into_inner().exit(&self.exited),
+                Err(TryLockError::WouldBlock) => {},
+            }
+        }
+    }
+
+    /// True once every input has been sent `Event::Exit`
+    ///
+    pub fn exited(&self) -> bool {
+        self.exited.load(Ordering::SeqCst) == self.groups.len()
+    }
+}
+
+// Sends `a` to the input at `idx` and `Event::Unchanged` to the other inputs
+// in its group, returns false if the topology has exited
+fn dispatch<A>(tx: &LinkTx<A>, idx: usize, a: &A, txs: &Dispatch, stopped: &AtomicBool) -> bool where
+    A: 'static + Send + Clone,
+{
+    let sent = {
+        let mut groups = txs.routes[idx].iter()
+            .map(|&g| txs.groups[g].lock().unwrap())
+            .collect::<Vec<_>>();
+
+        // The topology may have been stopped while we were waiting
+        // for the lock, in which case the data has nowhere to go
+        !stopped.load(Ordering::SeqCst) && send_event(tx, idx, a, &mut groups)
+    };
+
+    // Stopping doesn't wait for groups which are busy, so it may have skipped
+    // ours while we held them.  It's left to us to send them `Event::Exit`,
+    // checking once they've been released so no stop is missed
+    if stopped.load(Ordering::SeqCst) {
+        for &g in txs.routes[idx].iter() {
+            match txs.groups[g].lock() {
+                Ok(mut group) => group.exit(&txs.exited),
+                Err(e) => e.into_inner().exit(&txs.exited),
+            }
+        }
+        return false
+    }
+
+    sent
+}
+
+fn send_event<A>(tx: &LinkTx<A>, idx: usize, a: &A, groups: &mut [MutexGuard<Group>]) -> bool where
+    A: 'static + Send + Clone,
+{
+    for group in groups.iter_mut() {
+        for &mut (i, ref mut no_op) in group.no_ops.iter_mut() {
+            if i == idx {
+                if !tx.send(Event::Changed(a.clone())) { return false }
+            } else {
+                if no_op.send_no_change() { return false }
+            }
+        }
+    }
+
+    true
+}
+
+// Sends `Event::Exit` to every input, unless the topology has already been
+// stopped
+fn disconnect(txs: &Dispatch, stopped: &AtomicBool) {
+    txs.stop(stopped);
 }
 
 pub struct ReceiverInput<A> {
     rx: Receiver<A>,
-    tx: SyncSender<Event<A>>,
+    tx: LinkTx<A>,
+    node: Node,
 }
 
-impl<A> ReceiverInput<A> {
-    pub fn new(rx: Receiver<A>, tx: SyncSender<Event<A>>) -> ReceiverInput<A> {
+impl<A> ReceiverInput<A> where
+    A: 'static + Send + Clone,
+{
+    pub fn new(rx: Receiver<A>, tx: LinkTx<A>, node: Node) -> ReceiverInput<A> {
         ReceiverInput {
             rx: rx,
             tx: tx,
+            node,
         }
     }
 }
@@ -35,54 +262,514 @@ impl<A> RunInput for ReceiverInput<A> where
         Box::new(self.tx.clone())
     }
 
-    fn run(self: Box<Self>, idx: usize, txs: Arc<Mutex<Vec<Box<NoOp>>>>) {
-        debug!("SETUP: running ReceiverInput");
-        let inner = *self;
-        let ReceiverInput {rx, tx} = inner;
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    // Data is read by a thread of its own, which wakes the scheduler after
+    // passing it on.  Only one value is read ahead, so data is still left
+    // unread while the topology catches up.
+    fn wake_with(&mut self, scheduler: &Scheduler) {
+        let (tx, rx) = sync_channel(1);
+        let data = mem::replace(&mut self.rx, rx);
+        let scheduler = scheduler.clone();
+
+        spawn(format!("{} reader", self.tx.name()), move || {
+            loop {
+                match data.recv() {
+                    Ok(a) => {
+                        if tx.send(a).is_err() { return }
+                        scheduler.wake();
+                    },
+                    Err(_) => {
+                        drop(tx);
+                        scheduler.wake();
+                        return
+                    },
+                }
+            }
+        });
+    }
 
+    fn run(self: Box<Self>, idx: usize, txs: Arc<Dispatch>, stopped: Arc<AtomicBool>) {
+        debug!("SETUP: [{}] running ReceiverInput", self.tx.name());
+
+        // Waits for data a poll interval at a time, so the input exits soon
+        // after the topology has been stopped
         loop {
-            match rx.recv() {
+            if stopped.load(Ordering::SeqCst) {
+                info!("RUN: [{}] ReceiverInput stopped, exiting", self.tx.name());
+                return
+            }
+
+            match self.rx.recv_timeout(Duration::from_millis(POLL_INTERVAL_MS)) {
                 Ok(ref a) => {
-                    info!("RUN: ReceiverInput received data, sending");
-                    for (i, mut no_op_tx) in txs.lock().unwrap().iter_mut().enumerate() {
-                        if i == idx {
-                            match tx.send(Event::Changed(a.clone())) {
-                                Err(_) => return,
-                                _ => {},
-                            }
-                        } else {
-                            if no_op_tx.send_no_change() { return }
-                        }
-                    }
+                    info!("RUN: [{}] ReceiverInput received data, sending", self.tx.name());
+                    if !dispatch(&self.tx, idx, a, &txs, &stopped) { return }
                 },
+                Err(RecvTimeoutError::Timeout) => {},
                 Err(e) => {
-                    info!("RUN: ReceiverInput sending error {}, exiting", e);
-                    for no_op_tx in txs.lock().unwrap().iter() {
-                        no_op_tx.send_exit();
-                    }
+                    info!("RUN: [{}] ReceiverInput sending error {}, exiting", self.tx.name(), e);
+                    disconnect(&txs, &stopped);
                     return
                 },
             }
         }
     }
+
+    fn push(&mut self, idx: usize, a: Box<Any + Send>, txs: &Dispatch, stopped: &AtomicBool) -> bool {
+        match a.downcast::<A>() {
+            Ok(a) => {
+                info!("RUN: [{}] ReceiverInput pushed data, sending", self.tx.name());
+                dispatch(&self.tx, idx, &*a, txs, stopped)
+            },
+            Err(_) => panic!("input {} was pushed data of the wrong type", idx),
+        }
+    }
+
+    fn poll(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> Poll {
+        if stopped.load(Ordering::SeqCst) {
+            info!("RUN: [{}] ReceiverInput stopped, done", self.tx.name());
+            return Poll::Done
+        }
+
+        match self.rx.try_recv() {
+            Ok(ref a) => {
+                info!("RUN: [{}] ReceiverInput received data, sending", self.tx.name());
+                if dispatch(&self.tx, idx, a, txs, stopped) {
+                    Poll::Ready
+                } else {
+                    Poll::Done
+                }
+            },
+            Err(TryRecvError::Empty) => Poll::Idle(None),
+            Err(TryRecvError::Disconnected) => {
+                info!("RUN: [{}] ReceiverInput disconnected, done", self.tx.name());
+                disconnect(txs, stopped);
+                Poll::Done
+            },
+        }
+    }
 }
 
 
-impl<A> NoOp for SyncSender<Event<A>> where
-A: Send
+impl<A> NoOp for LinkTx<A> where
+A: 'static + Send
 {
     fn send_no_change(&mut self) -> bool {
-        info!("RUN: Sender sending Unchanged");
-        match self.send(Event::Unchanged) {
-            Err(_) => true,
-            _ => false,
-        }
+        info!("RUN: [{}] Sender sending Unchanged", self.name());
+        !self.send(Event::Unchanged)
     }
 
     fn send_exit(&self) {
-        info!("RUN: Sender sending Exit");
-        match self.send(Event::Exit) {
-            _ => {}
+        info!("RUN: [{}] Sender sending Exit", self.name());
+        self.send(Event::Exit);
+    }
+}
+
+/// An input which dispatches the time every `interval`, according to `clock`
+///
+/// Each tick is the time it was due rather than the time it was dispatched,
+/// and ticks which are missed (for instance because the topology was busy)
+/// are all dispatched once the input catches up.
+///
+pub struct TimerInput {
+    clock: Arc<Clock>,
+    interval: Duration,
+    next: Instant,
+    tx: LinkTx<Instant>,
+    node: Node,
+}
+
+impl TimerInput {
+    pub fn new(clock: Arc<Clock>, interval: Duration, start: Instant, tx: LinkTx<Instant>, node: Node) -> TimerInput {
+        TimerInput {
+            clock,
+            interval,
+            next: start + interval,
+            tx,
+            node,
+        }
+    }
+
+    // Dispatches the next tick, returns false if the topology has exited
+    fn tick(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> bool {
+        info!("RUN: [{}] TimerInput ticked, sending", self.tx.name());
+        let tick = self.next;
+        self.next = tick + self.interval;
+
+        dispatch(&self.tx, idx, &tick, txs, stopped)
+    }
+}
+
+impl RunInput for TimerInput {
+    fn boxed_no_op(&self) -> Box<NoOp> {
+        Box::new(self.tx.clone())
+    }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn run(mut self: Box<Self>, idx: usize, txs: Arc<Dispatch>, stopped: Arc<AtomicBool>) {
+        debug!("SETUP: [{}] running TimerInput", self.tx.name());
+
+        loop {
+            if stopped.load(Ordering::SeqCst) {
+                info!("RUN: [{}] TimerInput stopped, exiting", self.tx.name());
+                return
+            }
+
+            if self.clock.wait_until(self.next, Duration::from_millis(POLL_INTERVAL_MS)) && !self.tick(idx, &txs, &stopped) {
+                return
+            }
+        }
+    }
+
+    fn push(&mut self, idx: usize, _: Box<Any + Send>, _: &Dispatch, _: &AtomicBool) -> bool {
+        panic!("input {} doesn't receive data", idx)
+    }
+
+    fn poll(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> Poll {
+        if stopped.load(Ordering::SeqCst) {
+            info!("RUN: [{}] TimerInput stopped, done", self.tx.name());
+            return Poll::Done
+        }
+
+        let now = self.clock.now();
+        if now < self.next {
+            Poll::Idle(Some(until(now, self.next)))
+        } else if self.tick(idx, txs, stopped) {
+            Poll::Ready
+        } else {
+            Poll::Done
+        }
+    }
+}
+
+/// An input which dispatches the time since its last frame, aiming for one
+/// frame every `interval`
+///
+/// Frames which are missed, because the input was blocked or the clock
+/// jumped, are skipped rather than caught up on: the next frame's duration
+/// covers all of the time since the last one, and frames carry on from then.
+///
+/// If there's a `gate`, frames are only dispatched while the last value
+/// received from it was true.  Time spent paused isn't counted.
+///
+pub struct FpsInput {
+    clock: Arc<Clock>,
+    interval: Duration,
+    last: Instant,
+    next: Instant,
+    gate: Option<Receiver<bool>>,
+    running: bool,
+    tx: LinkTx<Duration>,
+    node: Node,
+}
+
+impl FpsInput {
+    pub fn new(clock: Arc<Clock>, interval: Duration, start: Instant, gate: Option<Receiver<bool>>, running: bool, tx: LinkTx<Duration>, node: Node) -> FpsInput {
+        FpsInput {
+            clock,
+            interval,
+            last: start,
+            next: start + interval,
+            gate,
+            running,
+            tx,
+            node,
+        }
+    }
+
+    // Pauses or resumes frames
+    fn set_running(&mut self, running: bool) {
+        if running && !self.running {
+            let now = self.clock.now();
+            self.last = now;
+            self.next = now + self.interval;
+        }
+        self.running = running;
+    }
+
+    // Handles any values the gate has sent
+    fn drain_gate(&mut self) {
+        loop {
+            let received = match self.gate {
+                Some(ref gate) => gate.try_recv(),
+                None => return,
+            };
+
+            match received {
+                Ok(running) => self.set_running(running),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    // The gate won't change again
+                    self.gate = None;
+                    return
+                },
+            }
+        }
+    }
+
+    // Dispatches a frame, returns false if the topology has exited
+    fn frame(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> bool {
+        info!("RUN: [{}] FpsInput frame, sending", self.tx.name());
+        let now = self.clock.now();
+        let delta = now - self.last;
+
+        self.last = now;
+        self.next += self.interval;
+        if self.next <= now {
+            self.next = now + self.interval;
+        }
+
+        dispatch(&self.tx, idx, &delta, txs, stopped)
+    }
+}
+
+impl RunInput for FpsInput {
+    fn boxed_no_op(&self) -> Box<NoOp> {
+        Box::new(self.tx.clone())
+    }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn run(mut self: Box<Self>, idx: usize, txs: Arc<Dispatch>, stopped: Arc<AtomicBool>) {
+        debug!("SETUP: [{}] running FpsInput", self.tx.name());
+
+        let poll = Duration::from_millis(POLL_INTERVAL_MS);
+        loop {
+            if stopped.load(Ordering::SeqCst) {
+                info!("RUN: [{}] FpsInput stopped, exiting", self.tx.name());
+                return
+            }
+
+            self.drain_gate();
+
+            if self.running {
+                if self.clock.wait_until(self.next, poll) && !self.frame(idx, &txs, &stopped) {
+                    return
+                }
+            } else {
+                let received = match self.gate {
+                    Some(ref gate) => gate.recv_timeout(poll),
+                    None => {
+                        thread::sleep(poll);
+                        continue
+                    },
+                };
+
+                match received {
+                    Ok(running) => self.set_running(running),
+                    Err(RecvTimeoutError::Timeout) => {},
+                    Err(RecvTimeoutError::Disconnected) => self.gate = None,
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, idx: usize, _: Box<Any + Send>, _: &Dispatch, _: &AtomicBool) -> bool {
+        panic!("input {} doesn't receive data", idx)
+    }
+
+    fn poll(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> Poll {
+        if stopped.load(Ordering::SeqCst) {
+            info!("RUN: [{}] FpsInput stopped, done", self.tx.name());
+            return Poll::Done
+        }
+
+        self.drain_gate();
+
+        let now = self.clock.now();
+        if !self.running {
+            Poll::Idle(None)
+        } else if now < self.next {
+            Poll::Idle(Some(until(now, self.next)))
+        } else if self.frame(idx, txs, stopped) {
+            Poll::Ready
+        } else {
+            Poll::Done
+        }
+    }
+}
+
+/// When a `TimedInput` dispatches the values it's sent
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timing {
+    /// Dispatch a value once the duration has passed without a newer one
+    Debounce(Duration),
+    /// Dispatch at most one value per duration, the first straight away and
+    /// the latest of any others at the end of the duration
+    Throttle(Duration),
+    /// Dispatch every value once the duration has passed
+    Delay(Duration),
+}
+
+/// An input which dispatches values sent to it with the time they were sent,
+/// according to `timing`
+///
+/// Values are sent by an `Async` runner with `AsyncSender::Timed`, and times
+/// are read from `clock`.
+///
+pub struct TimedInput<A> {
+    clock: Arc<Clock>,
+    timing: Timing,
+    rx: Receiver<(Instant, A)>,
+    tx: LinkTx<A>,
+    node: Node,
+    // Values waiting to be dispatched, with the time they're due
+    pending: VecDeque<(Instant, A)>,
+    // When the last value scheduled is due, for throttling
+    last: Option<Instant>,
+}
+
+impl<A> TimedInput<A> where
+    A: 'static + Send + Clone,
+{
+    pub fn new(clock: Arc<Clock>, timing: Timing, rx: Receiver<(Instant, A)>, tx: LinkTx<A>, node: Node) -> TimedInput<A> {
+        TimedInput {
+            clock,
+            timing,
+            rx,
+            tx,
+            node,
+            pending: VecDeque::new(),
+            last: None,
+        }
+    }
+
+    // Schedules a value sent at `at`
+    fn receive(&mut self, at: Instant, a: A) {
+        match self.timing {
+            Timing::Debounce(wait) => {
+                self.pending.clear();
+                self.pending.push_back((at + wait, a));
+            },
+            Timing::Throttle(interval) => {
+                match self.pending.back_mut() {
+                    // Replaces the value waiting for the end of the interval
+                    Some(&mut (due, ref mut held)) if due > at => {
+                        *held = a;
+                        return
+                    },
+                    _ => {},
+                }
+
+                let due = match self.last {
+                    Some(last) if last + interval > at => last + interval,
+                    _ => at,
+                };
+                self.last = Some(due);
+                self.pending.push_back((due, a));
+            },
+            Timing::Delay(by) => {
+                self.pending.push_back((at + by, a));
+            },
+        }
+    }
+
+    // The next value if it's due, removing it from `pending`
+    fn next_due(&mut self) -> Option<A> {
+        let now = self.clock.now();
+
+        match self.pending.front() {
+            Some(&(due, _)) if due <= now => {},
+            _ => return None,
+        }
+
+        self.pending.pop_front().map(|(_, a)| a)
+    }
+}
+
+impl<A> RunInput for TimedInput<A> where
+    A: 'static + Send + Clone,
+{
+    fn boxed_no_op(&self) -> Box<NoOp> {
+        Box::new(self.tx.clone())
+    }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn run(mut self: Box<Self>, idx: usize, txs: Arc<Dispatch>, stopped: Arc<AtomicBool>) {
+        debug!("SETUP: [{}] running TimedInput", self.tx.name());
+
+        loop {
+            if stopped.load(Ordering::SeqCst) {
+                info!("RUN: [{}] TimedInput stopped, exiting", self.tx.name());
+                return
+            }
+
+            while let Some(a) = self.next_due() {
+                info!("RUN: [{}] TimedInput value due, sending", self.tx.name());
+                if !dispatch(&self.tx, idx, &a, &txs, &stopped) { return }
+            }
+
+            // Waits for the next value to be sent or to become due, checking
+            // the clock at least every poll interval
+            let poll = Duration::from_millis(POLL_INTERVAL_MS);
+            let timeout = match self.pending.front() {
+                Some(&(due, _)) => {
+                    let now = self.clock.now();
+                    if due > now && due - now < poll { due - now } else { poll }
+                },
+                None => poll,
+            };
+
+            match self.rx.recv_timeout(timeout) {
+                Ok((at, a)) => self.receive(at, a),
+                Err(RecvTimeoutError::Timeout) => {},
+                Err(e) => {
+                    info!("RUN: [{}] TimedInput sending error {}, exiting", self.tx.name(), e);
+                    disconnect(&txs, &stopped);
+                    return
+                },
+            }
+        }
+    }
+
+    fn push(&mut self, idx: usize, _: Box<Any + Send>, _: &Dispatch, _: &AtomicBool) -> bool {
+        panic!("input {} doesn't receive data", idx)
+    }
+
+    fn poll(&mut self, idx: usize, txs: &Dispatch, stopped: &AtomicBool) -> Poll {
+        if stopped.load(Ordering::SeqCst) {
+            info!("RUN: [{}] TimedInput stopped, done", self.tx.name());
+            return Poll::Done
+        }
+
+        loop {
+            match self.rx.try_recv() {
+                Ok((at, a)) => self.receive(at, a),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    info!("RUN: [{}] TimedInput disconnected, done", self.tx.name());
+                    disconnect(txs, stopped);
+                    return Poll::Done
+                },
+            }
+        }
+
+        match self.next_due() {
+            Some(ref a) => {
+                info!("RUN: [{}] TimedInput value due, sending", self.tx.name());
+                if dispatch(&self.tx, idx, a, txs, stopped) {
+                    Poll::Ready
+                } else {
+                    Poll::Done
+                }
+            },
+            None => {
+                match self.pending.front() {
+                    Some(&(due, _)) => Poll::Idle(Some(until(self.clock.now(), due))),
+                    None => Poll::Idle(None),
+                }
+            },
         }
     }
 }
@@ -92,45 +779,57 @@ pub struct AckInput<A> where
 A: Send + Clone,
 {
     initial: A,
-    tx: SyncSender<Event<A>>,
+    tx: LinkTx<A>,
+    node: Node,
 }
 
 impl<A> AckInput<A> where 
     A: Send + Clone
 {
-    pub fn new(v: A, tx: SyncSender<Event<A>>) -> Self {
-        AckInput { initial: v, tx: tx}
+    pub fn new(v: A, tx: LinkTx<A>, node: Node) -> Self {
+        AckInput { initial: v, tx, node }
     }
 }
 
 impl<A> RunInput for AckInput<A> where
 A: 'static + Send + Clone,
 {
-    fn run(self: Box<Self>, _: usize, _: Arc<Mutex<Vec<Box<NoOp>>>>) {
+    fn run(self: Box<Self>, _: usize, _: Arc<Dispatch>, _: Arc<AtomicBool>) {
         // Nothing to do here - all the work is done on NoOp
     }
 
+    fn poll(&mut self, _: usize, _: &Dispatch, _: &AtomicBool) -> Poll {
+        Poll::Done
+    }
+
+    fn push(&mut self, idx: usize, _: Box<Any + Send>, _: &Dispatch, _: &AtomicBool) -> bool {
+        panic!("input {} doesn't receive data", idx)
+    }
+
     fn boxed_no_op(&self) -> Box<NoOp> {
         Box::new(self.clone())
     }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn on_every_event(&self) -> bool {
+        true
+    }
 }
 
 impl<A> NoOp for AckInput<A> where
-A: Send + Clone
+A: 'static + Send + Clone
 {
     fn send_no_change(&mut self) -> bool {
-        info!("RUN: Ack sending value");
-        match self.tx.send(Event::Changed(self.initial.clone())) {
-            Err(_) => true,
-            _ => false,
-        }
+        info!("RUN: [{}] Ack sending value", self.tx.name());
+        !self.tx.send(Event::Changed(self.initial.clone()))
     }
 
     fn send_exit(&self) {
-        info!("RUN: Ack sending Exit");
-        match self.tx.send(Event::Exit) {
-            _ => {}
-        }
+        info!("RUN: [{}] Ack sending Exit", self.tx.name());
+        self.tx.send(Event::Exit);
     }
 }
 
@@ -140,15 +839,16 @@ R: rand::Rng + Clone + Send,
 A: Send + Clone + rand::Rand,
 {
     rng: R,
-    tx: SyncSender<Event<A>>,
+    tx: LinkTx<A>,
+    node: Node,
 }
 
 impl<R, A> RngInput<R, A> where 
 R: rand::Rng + Clone + Send,
 A: Send + Clone + rand::Rand,
 {
-    pub fn new(rng: R, tx: SyncSender<Event<A>>) -> Self {
-        RngInput { rng: rng, tx: tx}
+    pub fn new(rng: R, tx: LinkTx<A>, node: Node) -> Self {
+        RngInput { rng, tx, node }
     }
 }
 
@@ -156,32 +856,43 @@ impl<R, A> RunInput for RngInput<R, A> where
 R: 'static + rand::Rng + Clone + Send,
 A: 'static + Send + Clone + rand::Rand,
 {
-    fn run(self: Box<Self>, _: usize, _: Arc<Mutex<Vec<Box<NoOp>>>>) {
+    fn run(self: Box<Self>, _: usize, _: Arc<Dispatch>, _: Arc<AtomicBool>) {
         // Nothing to do here - all the work is done on NoOp
     }
 
+    fn poll(&mut self, _: usize, _: &Dispatch, _: &AtomicBool) -> Poll {
+        Poll::Done
+    }
+
+    fn push(&mut self, idx: usize, _: Box<Any + Send>, _: &Dispatch, _: &AtomicBool) -> bool {
+        panic!("input {} doesn't receive data", idx)
+    }
+
     fn boxed_no_op(&self) -> Box<NoOp> {
         Box::new(self.clone())
     }
+
+    fn describe(&self) -> Node {
+        self.node.clone()
+    }
+
+    fn on_every_event(&self) -> bool {
+        true
+    }
 }
 
 impl<R, A> NoOp for RngInput<R, A> where
 R: rand::Rng + Clone + Send,
-A: Send + Clone + rand::Rand,
+A: 'static + Send + Clone + rand::Rand,
 {
     fn send_no_change(&mut self) -> bool {
-        info!("RUN: Rng sending value");
+        info!("RUN: [{}] Rng sending value", self.tx.name());
         let a = self.rng.gen();
-        match self.tx.send(Event::Changed(a)) {
-            Err(_) => true,
-            _ => false,
-        }
+        !self.tx.send(Event::Changed(a))
     }
 
     fn send_exit(&self) {
-        info!("RUN: Rng sending Exit");
-        match self.tx.send(Event::Exit) {
-            _ => {}
-        }
+        info!("RUN: [{}] Rng sending Exit", self.tx.name());
+        self.tx.send(Event::Exit);
     }
 }
@@ -0,0 +1,281 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::*;
+
+use super::super::{Value, Event, Signal, SignalType, Push, Config, NodeMetrics, NodeRole};
+use super::link::Pumps;
+use executor::Executor;
+
+/// Runs the body of a node which joins several signals in lockstep
+///
+/// Joins read one event from each of their inputs (created with `input`)
+/// per global event, and their body is run by whichever input completes an
+/// event.  With `Executor::ThreadPerNode`, `run` blocks the calling thread,
+/// which receives from the links of every parent as their events arrive.
+/// Otherwise it returns immediately, and the parents are run by the
+/// topology's `Scheduler`.
+///
+pub struct Join<B> {
+    config: Config,
+    name: String,
+    metrics: NodeMetrics,
+    inner: Arc<JoinInner<B>>,
+    // The number of inputs added so far
+    inputs: Cell<usize>,
+    // Set if the join receives from its parents' links itself, rather than
+    // leaving them to an enclosing join
+    pumping: bool,
+}
+
+struct JoinInner<B> {
+    metrics: NodeMetrics,
+    state: Mutex<JoinState<B>>,
+}
+
+struct JoinState<B> {
+    ready: Vec<Box<Fn() -> bool + Send>>,
+    step: Option<Box<FnMut() -> Option<Event<B>> + Send>>,
+    target: Option<Box<Push<B> + Send>>,
+}
+
+impl<B> Join<B> where
+    B: 'static + Send,
+{
+    /// Create a join for a node of the given kind
+    ///
+    pub fn new(config: &Config, kind: &str) -> Join<B> {
+        let name = config.node_name(kind).to_string();
+        let metrics = config.metrics().register(&name, NodeRole::Node);
+        let pumping = match config.executor {
+            Executor::ThreadPerNode => Pumps::start(),
+            _ => false,
+        };
+
+        Join {
+            config: config.clone(),
+            name,
+            metrics: metrics.clone(),
+            inner: Arc::new(JoinInner {
+                metrics,
+                state: Mutex::new(JoinState {
+                    ready: Vec::new(),
+                    step: None,
+                    target: None,
+                }),
+            }),
+            inputs: Cell::new(0),
+            pumping,
+        }
+    }
+
+    /// The name of the node the join is for
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The counters kept for the node the join is for
+    ///
+    /// Joins count the events they push, while the time spent in the node's
+    /// function should be measured by the node itself.
+    ///
+    pub fn metrics(&self) -> NodeMetrics {
+        self.metrics.clone()
+    }
+
+    /// Add an input to the join
+    ///
+    /// Dynamic parents push into a queue which is read by the join's body.
+    /// They're run by the thread running the join, or by the topology's
+    /// `Scheduler` when it isn't given a thread.  Constant parents are
+    /// cached and always read as `Value::Unchanged`.
+    ///
+    /// The counters for each dynamic input are kept under the join's name,
+    /// as `NodeRole::JoinInput` with the input's position.
+    ///
+    pub fn input<A>(&self, parent: Box<Signal<A>>) -> Lockstep<A> where
+        A: 'static + Send + Clone,
+    {
+        let index = self.inputs.get();
+        self.inputs.set(index + 1);
+
+        match parent.initial() {
+            SignalType::Constant(a) => Lockstep { name: self.name.clone(), metrics: None, queue: None, last: a },
+            SignalType::Dynamic(a) => {
+                let metrics = self.config.metrics().register(&self.name, NodeRole::JoinInput(index));
+                let queue = Arc::new(Mutex::new(VecDeque::new()));
+
+                let ready = queue.clone();
+                self.inner.state.lock().unwrap().ready.push(Box::new(move || {
+                    !ready.lock().unwrap().is_empty()
+                }));
+
+                parent.push_to(Some(Box::new(QueuePusher {
+                    name: self.name.clone(),
+                    metrics: metrics.clone(),
+                    queue: queue.clone(),
+                    join: self.inner.clone(),
+                    exited: false,
+                })));
+
+                Lockstep { name: self.name.clone(), metrics: Some(metrics), queue: Some(queue), last: a }
+            },
+        }
+    }
+
+    /// Run the join, pushing the events returned by `step` to `target`
+    ///
+    /// `step` should read once from each of the join's inputs and return the
+    /// resulting event, or `None` once one of them has exited.
+    ///
+    pub fn run<S>(self, target: Option<Box<Push<B> + Send>>, step: S) where
+        S: 'static + Send + FnMut() -> Option<Event<B>>,
+    {
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            state.step = Some(Box::new(step));
+            state.target = target;
+        }
+
+        if !self.pumping { return }
+
+        let inner = self.inner.clone();
+        Pumps::take().run(|| inner.state.lock().unwrap().step.is_some());
+
+        // Every parent has gone without exiting, let the rest of the
+        // topology know
+        let mut state = self.inner.state.lock().unwrap();
+        if let (Some(_), Some(t)) = (state.step.take(), state.target.as_mut()) {
+            t.push(Event::Exit);
+        }
+        state.ready.clear();
+        state.step = None;
+        state.target = None;
+    }
+}
+
+trait Trigger: Send + Sync {
+    fn trigger(&self);
+}
+
+impl<B> Trigger for JoinInner<B> where
+    B: 'static + Send,
+{
+    // Runs the join's body for as long as each input has an event waiting
+    fn trigger(&self) {
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+
+        loop {
+            if !state.ready.iter().all(|ready| ready()) { return }
+
+            let event = match state.step {
+                Some(ref mut step) => step(),
+                None => return,
+            };
+
+            match event {
+                Some(event) => {
+                    self.metrics.record(&event);
+                    if let Some(ref mut t) = state.target {
+                        t.push(event);
+                    }
+                },
+                None => {
+                    // A parent has exited, let the rest of the topology know
+                    if let Some(ref mut t) = state.target {
+                        t.push(Event::Exit);
+                    }
+
+                    state.ready.clear();
+                    state.step = None;
+                    state.target = None;
+                    return
+                },
+            }
+        }
+    }
+}
+
+/// One input of a lockstep join
+///
+/// Joins must call `recv` on each of their inputs once per event to stay in
+/// sync with the rest of the topology.
+///
+pub struct Lockstep<A> {
+    name: String,
+    metrics: Option<NodeMetrics>,
+    // None for constant parents
+    queue: Option<Arc<Mutex<VecDeque<Event<A>>>>>,
+    last: A,
+}
+
+impl<A> Lockstep<A> where
+    A: 'static + Send + Clone,
+{
+    /// Receive the next event, returns `None` once the parent has exited
+    ///
+    pub fn recv(&mut self) -> Option<Value<A>> {
+        let event = match self.queue {
+            Some(ref queue) => queue.lock().unwrap().pop_front(),
+            None => return Some(Value::Unchanged(self.last.clone())),
+        };
+
+        if let (Some(_), Some(metrics)) = (&event, &self.metrics) {
+            metrics.dequeued();
+        }
+
+        match event {
+            Some(Event::Changed(a)) => {
+                info!("RUN: [{}] Lockstep using changed value", self.name);
+                self.last = a.clone();
+                Some(Value::Changed(a))
+            },
+            Some(Event::Unchanged) => {
+                info!("RUN: [{}] Lockstep using cached value", self.name);
+                Some(Value::Unchanged(self.last.clone()))
+            },
+            Some(Event::Exit) => None,
+            None => None,
+        }
+    }
+}
+
+// Passed up the 'push_to' chain, finalizes by queueing the event and running
+// the join if it was the last one needed
+struct QueuePusher<A> {
+    name: String,
+    metrics: NodeMetrics,
+    queue: Arc<Mutex<VecDeque<Event<A>>>>,
+    join: Arc<Trigger>,
+    exited: bool,
+}
+
+impl<A> Push<A> for QueuePusher<A> where
+    A: 'static + Send,
+{
+    fn push(&mut self, event: Event<A>) {
+        debug!("[{}] Join::QueuePusher::push", self.name);
+        self.metrics.record(&event);
+        self.metrics.enqueued();
+
+        if let Event::Exit = event {
+            self.exited = true;
+        }
+
+        self.queue.lock().unwrap().push_back(event);
+        self.join.trigger();
+    }
+}
+
+// A parent dropped without exiting counts as having exited, so the join
+// doesn't wait on it forever
+impl<A> Drop for QueuePusher<A> {
+    fn drop(&mut self) {
+        if self.exited { return }
+
+        self.exited = true;
+        self.queue.lock().unwrap().push_back(Event::Exit);
+        self.join.trigger();
+    }
+}
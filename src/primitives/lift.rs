@@ -1,6 +1,6 @@
 use std::marker::*;
 
-use super::super::{Event, Signal, SignalExt, SignalType, Push, Config};
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole};
 
 /// The result of a `lift` operation
 ///
@@ -48,17 +48,24 @@ impl<F, A, B> Signal<B> for LiftSignal<F, A, B> where
         self.initial.clone()
     }
 
-    fn push_to(self: Box<Self>, target: Option<Box<Push<B>>>) {
+    fn describe(&self) -> Node {
+        Node::new("lift", self.config.name(), self.initial.is_constant(), vec![self.parent.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
         let inner = *self;
-        let LiftSignal { config: _, parent, f, initial: _ } = inner;
+        let LiftSignal { config, parent, f, initial: _ } = inner;
 
         match target {
             Some(t) => {
-                debug!("SETUP: Sending to target Some");
+                debug!("SETUP: [{}] Sending to target Some", config.node_name("lift"));
                 parent.push_to(
                     Some(
                         Box::new(
                             LiftPusher {
+                                name: config.node_name("lift").to_string(),
+                                metrics: config.metrics().register(config.node_name("lift"), NodeRole::Node),
+                                config,
                                 child: Some(t),
                                 f: f,
                                 marker: PhantomData,
@@ -69,11 +76,14 @@ impl<F, A, B> Signal<B> for LiftSignal<F, A, B> where
             }
 
             None => {
-                debug!("SETUP: Sending to target None");
+                debug!("SETUP: [{}] Sending to target None", config.node_name("lift"));
                 parent.push_to(
                     Some(
                         Box::new(
                             LiftPusher {
+                                name: config.node_name("lift").to_string(),
+                                metrics: config.metrics().register(config.node_name("lift"), NodeRole::Node),
+                                config,
                                 child: None,
                                 f: f,
                                 marker: PhantomData,
@@ -91,12 +101,25 @@ impl<F, A, B> SignalExt<B> for LiftSignal<F, A, B> where
     B: 'static + Send + Clone,
 {}
 
+impl<F, A, B> Configurable for LiftSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
 struct LiftPusher<F, A, B> where
     F: 'static + Send + Fn(A) -> B,
     A: 'static + Send + Clone,
     B: 'static + Send + Clone,
 {
-    child: Option<Box<Push<B>>>,
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<B> + Send>>,
     f: F,
     marker: PhantomData<A>,
 }
@@ -107,18 +130,22 @@ impl<F, A, B> Push<A> for LiftPusher<F, A, B> where
     B: 'static + Send + Clone,
 {
     fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
         let out = match event {
             Event::Changed(a) => {
-                info!("RUN: LiftPusher handling Event::Changed");
-                let b = (self.f)(a);
-                Event::Changed(b)
+                info!("RUN: [{}] LiftPusher handling Event::Changed", self.name);
+                match self.metrics.time(|| self.config.supervisor().guard(&self.name, || (self.f)(a))) {
+                    Some(b) => Event::Changed(b),
+                    None => Event::Unchanged,
+                }
             },
             Event::Unchanged => {
-                info!("RUN: LiftPusher handling Event::Unchanged");
+                info!("RUN: [{}] LiftPusher handling Event::Unchanged", self.name);
                 Event::Unchanged
             },
             Event::Exit => {
-                info!("RUN: LiftPusher handling Event::Exit");
+                info!("RUN: [{}] LiftPusher handling Event::Exit", self.name);
                 Event::Exit
             },
         };
@@ -1,7 +1,5 @@
-use std::thread;
-use std::sync::mpsc::*;
-
-use super::super::{Value, Event, Signal, SignalExt, SignalType, Push, Config};
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::Join;
 
 /// The result of a `lift2` operation
 ///
@@ -67,97 +65,44 @@ impl<F, A, B, C> Signal<C> for Lift2Signal<F, A, B, C> where
         self.initial.clone()
     }
 
-    fn push_to(self: Box<Self>, mut target: Option<Box<Push<C>>>) {
+    fn describe(&self) -> Node {
+        Node::new("lift2", self.config.name(), self.initial.is_constant(), vec![self.left.describe(), self.right.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<C> + Send>>) {
         let inner = *self;
         let Lift2Signal {config, left, right, f, initial: _} = inner;
 
-        let (left_tx, left_rx) = sync_channel(config.buffer_size.clone());
-        let (right_tx, right_rx) = sync_channel(config.buffer_size.clone());
-        let left_initial = left.initial();
-        let right_initial = right.initial();
-
-        let mut last_l = match left.initial() {
-            SignalType::Constant(l) => l,
-            SignalType::Dynamic(l) => {
-                thread::spawn(move || {
-                    let pusher = InputPusher {
-                        tx: left_tx,
-                    };
-                    left.push_to(Some(Box::new(pusher)));
-                });
-
-                l
-            },
-        };
-
-        let mut last_r = match right.initial().clone() {
-            SignalType::Constant(r) => r,
-            SignalType::Dynamic(r) => {
-                thread::spawn(move || {
-                    let pusher = InputPusher {
-                        tx: right_tx,
-                    };
-                    right.push_to(Some(Box::new(pusher)));
-                });
-
-                r
-            },
-        };
+        let join = Join::new(&config, "lift2");
+        let metrics = join.metrics();
+        let name = join.name().to_string();
+        let mut left = join.input(left);
+        let mut right = join.input(right);
 
-        loop {
-            let mut any_changed = false;
-
-            let l = match left_initial {
-                SignalType::Constant(ref l) => Value::Unchanged(l.clone()),
-                SignalType::Dynamic(_) => {
-                    match left_rx.recv() {
-                        Ok(Event::Changed(l)) => {
-                            info!("RUN: Lift2 using changed Left value");
-                            any_changed = true;
-                            last_l = l.clone();
-                            Value::Changed(l)
-                        },
-                        Ok(Event::Unchanged) => {
-                            info!("RUN: Lift2 using cached Left value");
-                            Value::Unchanged(last_l.clone())
-                        },
-                        Ok(Event::Exit) => return,
-                        Err(_) => return,
-                    }
-                }
+        join.run(target, move || {
+            let l = match left.recv() {
+                Some(l) => l,
+                None => return None,
             };
 
-            let r = match right_initial {
-                SignalType::Constant(ref r) => Value::Unchanged(r.clone()),
-                SignalType::Dynamic(_) => {
-                    match right_rx.recv() {
-                        Ok(Event::Changed(r)) => {
-                            info!("RUN: Lift2 using changed Right value");
-                            any_changed = true;
-                            last_r = r.clone();
-                            Value::Changed(r)
-                        },
-                        Ok(Event::Unchanged) => {
-                            info!("RUN: Lift2 using cached Right value");
-                            Value::Unchanged(last_r.clone())
-                        },
-                        Ok(Event::Exit) => return,
-                        Err(_) => return,
-                    }
-                }
+            let r = match right.recv() {
+                Some(r) => r,
+                None => return None,
             };
 
-            let c = if any_changed {
-                Event::Changed(f(l,r))
-            } else {
-                Event::Unchanged
+            let c = match (&l, &r) {
+                (&Value::Unchanged(_), &Value::Unchanged(_)) => Event::Unchanged,
+                _ => {
+                    info!("RUN: [{}] Lift2 computing changed value", name);
+                    match metrics.time(|| config.supervisor().guard(&name, || f(l, r))) {
+                        Some(c) => Event::Changed(c),
+                        None => Event::Unchanged,
+                    }
+                },
             };
 
-            match target {
-                Some(ref mut t) => t.push(c),
-                None => {},
-            }
-        }
+            Some(c)
+        });
     }
 }
 impl<F, A, B, C> SignalExt<C> for Lift2Signal<F, A, B, C> where
@@ -167,20 +112,13 @@ impl<F, A, B, C> SignalExt<C> for Lift2Signal<F, A, B, C> where
     C: 'static + Send + Clone,
 {}
 
-// Passed up the 'push_to' chain, finalizes by sending to a channel
-struct InputPusher<A> {
-    tx: SyncSender<Event<A>>,
-}
-
-impl<A> Push<A> for InputPusher<A> where
-    A: 'static + Send,
+impl<F, A, B, C> Configurable for Lift2Signal<F, A, B, C> where
+    F: 'static + Send + Fn(Value<A>, Value<B>) -> C,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    C: 'static + Send + Clone,
 {
-    fn push(&mut self, event: Event<A>) {
-        debug!("Lift2::InputPusher::push");
-
-        match self.tx.send(event) {
-            Err(e) => { debug!("Lift2::InputPusher received error {}", e) },
-            _ => {},
-        }
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
     }
 }
@@ -0,0 +1,121 @@
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::{Join, Lockstep};
+
+/// The result of a `lift_all` operation
+///
+pub struct LiftAllSignal<F, A, B> where
+    F: 'static + Send + Fn(Vec<Value<A>>) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    config: Config,
+    parents: Vec<Box<Signal<A>>>,
+    f: F,
+    initial: SignalType<B>,
+}
+
+impl<F, A, B> LiftAllSignal<F, A, B> where
+    F: 'static + Send + Fn(Vec<Value<A>>) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    pub fn new(config: Config, parents: Vec<Box<Signal<A>>>, f: F) -> Self {
+        let mut any_dynamic = false;
+
+        let values = parents.iter().map(|p| {
+            match p.initial() {
+                SignalType::Constant(a) => Value::Changed(a),
+                SignalType::Dynamic(a) => {
+                    any_dynamic = true;
+                    Value::Changed(a)
+                },
+            }
+        }).collect();
+
+        let initial = if any_dynamic {
+            SignalType::Dynamic(f(values))
+        } else {
+            SignalType::Constant(f(values))
+        };
+
+        LiftAllSignal {
+            config,
+            parents,
+            f,
+            initial,
+        }
+    }
+}
+
+impl<F, A, B> Signal<B> for LiftAllSignal<F, A, B> where
+    F: 'static + Send + Fn(Vec<Value<A>>) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<B> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("lift_all", self.config.name(), self.initial.is_constant(), self.parents.iter().map(|p| p.describe()).collect())
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
+        let inner = *self;
+        let LiftAllSignal {config, parents, f, initial: _} = inner;
+
+        let join = Join::new(&config, "lift_all");
+        let metrics = join.metrics();
+        let name = join.name().to_string();
+        let mut inputs = parents.into_iter()
+            .map(|p| join.input(p))
+            .collect::<Vec<Lockstep<A>>>();
+
+        join.run(target, move || {
+            let mut any_changed = false;
+            let mut values = Vec::with_capacity(inputs.len());
+
+            for input in inputs.iter_mut() {
+                match input.recv() {
+                    Some(Value::Changed(a)) => {
+                        any_changed = true;
+                        values.push(Value::Changed(a));
+                    },
+                    Some(v) => values.push(v),
+                    None => return None,
+                }
+            }
+
+            let out = if any_changed {
+                info!("RUN: [{}] LiftAll computing changed value", name);
+                match metrics.time(|| config.supervisor().guard(&name, || f(values))) {
+                    Some(b) => Event::Changed(b),
+                    None => Event::Unchanged,
+                }
+            } else {
+                Event::Unchanged
+            };
+
+            Some(out)
+        });
+    }
+}
+impl<F, A, B> SignalExt<B> for LiftAllSignal<F, A, B> where
+    F: 'static + Send + Fn(Vec<Value<A>>) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{}
+
+impl<F, A, B> Configurable for LiftAllSignal<F, A, B> where
+    F: 'static + Send + Fn(Vec<Value<A>>) -> B,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
@@ -0,0 +1,138 @@
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::Join;
+
+// Generates the signal type for an n-ary `lift`.  Each parent is received from
+// in a single join, so the whole join only costs one thread regardless of its
+// width.
+//
+macro_rules! lift_n {
+    ($name:ident, $node:expr, $out:ident, $(($t:ident, $field:ident)),+) => {
+        /// The result of an n-ary `lift` operation
+        ///
+        pub struct $name<F, $($t,)+ $out> where
+            F: 'static + Send + Fn($(Value<$t>),+) -> $out,
+            $($t: 'static + Send + Clone,)+
+            $out: 'static + Send + Clone,
+        {
+            config: Config,
+            $($field: Box<Signal<$t>>,)+
+            f: F,
+            initial: SignalType<$out>,
+        }
+
+        impl<F, $($t,)+ $out> $name<F, $($t,)+ $out> where
+            F: 'static + Send + Fn($(Value<$t>),+) -> $out,
+            $($t: 'static + Send + Clone,)+
+            $out: 'static + Send + Clone,
+        {
+            // Takes one argument per parent signal, so the wider arities go
+            // past clippy's argument limit
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(config: Config, $($field: Box<Signal<$t>>,)+ f: F) -> Self {
+                let mut any_dynamic = false;
+
+                let v = f($(
+                    match $field.initial() {
+                        SignalType::Constant(v) => Value::Changed(v),
+                        SignalType::Dynamic(v) => {
+                            any_dynamic = true;
+                            Value::Changed(v)
+                        },
+                    }
+                ),+);
+
+                let initial = if any_dynamic {
+                    SignalType::Dynamic(v)
+                } else {
+                    SignalType::Constant(v)
+                };
+
+                $name {
+                    config,
+                    $($field,)+
+                    f,
+                    initial,
+                }
+            }
+        }
+
+        impl<F, $($t,)+ $out> Signal<$out> for $name<F, $($t,)+ $out> where
+            F: 'static + Send + Fn($(Value<$t>),+) -> $out,
+            $($t: 'static + Send + Clone,)+
+            $out: 'static + Send + Clone,
+        {
+            fn config(&self) -> Config {
+                self.config.clone()
+            }
+
+            fn initial(&self) -> SignalType<$out> {
+                self.initial.clone()
+            }
+
+            fn describe(&self) -> Node {
+                Node::new($node, self.config.name(), self.initial.is_constant(), vec![$(self.$field.describe()),+])
+            }
+
+            fn push_to(self: Box<Self>, target: Option<Box<Push<$out> + Send>>) {
+                let inner = *self;
+                let $name { config, $($field,)+ f, initial: _ } = inner;
+
+                let join = Join::new(&config, $node);
+                let metrics = join.metrics();
+                let name = join.name().to_string();
+                $(let mut $field = join.input($field);)+
+
+                join.run(target, move || {
+                    let mut any_changed = false;
+
+                    // Shadows each `Lockstep` with the value received from it
+                    // for the rest of this event
+                    $(
+                        let $field = match $field.recv() {
+                            Some(Value::Changed(v)) => {
+                                any_changed = true;
+                                Value::Changed(v)
+                            },
+                            Some(v) => v,
+                            None => return None,
+                        };
+                    )+
+
+                    let out = if any_changed {
+                        info!("RUN: [{}] {} computing changed value", name, stringify!($name));
+                        match metrics.time(|| config.supervisor().guard(&name, || f($($field),+))) {
+                            Some(v) => Event::Changed(v),
+                            None => Event::Unchanged,
+                        }
+                    } else {
+                        Event::Unchanged
+                    };
+
+                    Some(out)
+                });
+            }
+        }
+        impl<F, $($t,)+ $out> SignalExt<$out> for $name<F, $($t,)+ $out> where
+            F: 'static + Send + Fn($(Value<$t>),+) -> $out,
+            $($t: 'static + Send + Clone,)+
+            $out: 'static + Send + Clone,
+        {}
+
+        impl<F, $($t,)+ $out> Configurable for $name<F, $($t,)+ $out> where
+            F: 'static + Send + Fn($(Value<$t>),+) -> $out,
+            $($t: 'static + Send + Clone,)+
+            $out: 'static + Send + Clone,
+        {
+            fn config_mut(&mut self) -> &mut Config {
+                &mut self.config
+            }
+        }
+    }
+}
+
+lift_n!(Lift3Signal, "lift3", B, (A1, a1), (A2, a2), (A3, a3));
+lift_n!(Lift4Signal, "lift4", B, (A1, a1), (A2, a2), (A3, a3), (A4, a4));
+lift_n!(Lift5Signal, "lift5", B, (A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5));
+lift_n!(Lift6Signal, "lift6", B, (A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5), (A6, a6));
+lift_n!(Lift7Signal, "lift7", B, (A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5), (A6, a6), (A7, a7));
+lift_n!(Lift8Signal, "lift8", B, (A1, a1), (A2, a2), (A3, a3), (A4, a4), (A5, a5), (A6, a6), (A7, a7), (A8, a8));
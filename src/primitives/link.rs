@@ -0,0 +1,722 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::*;
+use std::sync::atomic::*;
+use std::sync::mpsc::TryRecvError;
+
+use super::super::{Event, Push, Config, BackpressurePolicy, NodeMetrics, NodeRole};
+use executor::{Executor, Job, Scheduler};
+
+/// The sending and receiving ends of a link
+///
+pub type LinkEnds<A> = (LinkTx<A>, LinkRx<A>);
+
+/// Create a link carrying events from one node to another
+///
+/// With `Executor::ThreadPerNode` links are queues bounded by
+/// `Config::buffer_size`, which behave like a `sync_channel` of that size,
+/// and the receiving end runs in its own thread.  Otherwise they're
+/// mailboxes which are run by the topology's `Scheduler`, holding at least
+/// one event; a full mailbox stops the scheduler reading from inputs rather
+/// than blocking the sender.
+///
+/// `Config::backpressure` decides what a link does when it's full.  Lossy
+/// links never block the sender, and drop values rather than events.
+/// `Event::Exit` never blocks the sender either.
+///
+/// Links are named after the node they're created for, or `kind` if it
+/// hasn't been named.  They count the events they carry and how many are
+/// waiting to be received.
+///
+pub fn link<A>(config: &Config, kind: &str) -> LinkEnds<A> where
+    A: 'static + Send,
+{
+    let name = config.node_name(kind).to_string();
+    let metrics = config.metrics().register(&name, NodeRole::Link);
+
+    let policy = config.backpressure;
+    let capacity = match policy {
+        BackpressurePolicy::Block => config.buffer_size,
+        BackpressurePolicy::Latest => 1,
+        _ => cmp::max(config.buffer_size, 1),
+    };
+
+    let (tx_end, rx_end) = match config.executor {
+        Executor::ThreadPerNode => {
+            let queue = Arc::new(Queue::new(policy, capacity));
+            (TxEnd::Queue(Arc::new(QueueTx(queue.clone()))), RxEnd::Queue(QueueRx(queue)))
+        },
+        _ => {
+            let capacity = cmp::max(capacity, 1);
+            let mailbox = Arc::new(Mailbox::new(config.scheduler().clone(), name.clone(), metrics.clone(), policy, capacity));
+            (TxEnd::Mailbox(mailbox.clone()), RxEnd::Mailbox(mailbox))
+        },
+    };
+
+    let tx = LinkTx { name: name.clone(), metrics: metrics.clone(), end: tx_end };
+    let rx = LinkRx { name, metrics, end: rx_end };
+    (tx, rx)
+}
+
+/// The sending end of a link
+///
+pub struct LinkTx<A> {
+    name: String,
+    metrics: NodeMetrics,
+    end: TxEnd<A>,
+}
+
+enum TxEnd<A> {
+    Queue(Arc<QueueTx<A>>),
+    Mailbox(Arc<Mailbox<A>>),
+}
+
+impl<A> LinkTx<A> where
+    A: 'static + Send,
+{
+    /// The name of the link
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// True if the link drops events rather than blocking when it's full
+    ///
+    pub fn is_lossy(&self) -> bool {
+        match self.end {
+            TxEnd::Queue(ref tx) => tx.0.policy != BackpressurePolicy::Block,
+            TxEnd::Mailbox(ref mailbox) => mailbox.policy != BackpressurePolicy::Block,
+        }
+    }
+
+    /// Send an event, returns false if the receiving end has exited
+    ///
+    pub fn send(&self, event: Event<A>) -> bool {
+        match self.end {
+            TxEnd::Queue(ref tx) => {
+                let queue = &tx.0;
+                let mut events = queue.events.lock().unwrap();
+                if queue.closed.load(Ordering::SeqCst) { return false }
+
+                let exit = matches!(event, Event::Exit);
+
+                self.metrics.enqueued();
+                if !events.offer(event, queue.policy, queue.capacity) {
+                    self.metrics.dropped();
+                }
+                queue.ready.notify_one();
+                queue.wake();
+
+                // Blocking queues wait until they're back within capacity,
+                // which for a capacity of 0 is once the event is received
+                if queue.policy == BackpressurePolicy::Block && !exit {
+                    while events.len() > queue.capacity {
+                        if queue.closed.load(Ordering::SeqCst) { return false }
+                        events = queue.space.wait(events).unwrap();
+                    }
+                }
+                true
+            },
+            TxEnd::Mailbox(ref mailbox) => {
+                if mailbox.closed.load(Ordering::SeqCst) { return false }
+
+                let mut queue = mailbox.queue.lock().unwrap();
+                self.metrics.enqueued();
+                mailbox.scheduler.queued();
+                if !queue.offer(event, mailbox.policy, mailbox.capacity) {
+                    self.metrics.dropped();
+                }
+
+                // Blocking mailboxes can't hold up the sender, which may be
+                // the worker that would drain them, so the scheduler stops
+                // reading from inputs instead
+                if mailbox.policy == BackpressurePolicy::Block && queue.len() >= mailbox.capacity && !mailbox.full.swap(true, Ordering::SeqCst) {
+                    mailbox.scheduler.filled();
+                }
+                drop(queue);
+
+                if !mailbox.scheduled.swap(true, Ordering::SeqCst) {
+                    mailbox.scheduler.schedule(mailbox.clone());
+                }
+                true
+            },
+        }
+    }
+}
+
+impl<A> Clone for LinkTx<A> {
+    fn clone(&self) -> LinkTx<A> {
+        let end = match self.end {
+            TxEnd::Queue(ref tx) => TxEnd::Queue(tx.clone()),
+            TxEnd::Mailbox(ref mailbox) => TxEnd::Mailbox(mailbox.clone()),
+        };
+
+        LinkTx { name: self.name.clone(), metrics: self.metrics.clone(), end }
+    }
+}
+
+/// The receiving end of a link
+///
+pub struct LinkRx<A> {
+    name: String,
+    metrics: NodeMetrics,
+    end: RxEnd<A>,
+}
+
+enum RxEnd<A> {
+    Queue(QueueRx<A>),
+    Mailbox(Arc<Mailbox<A>>),
+}
+
+impl<A> LinkRx<A> where
+    A: 'static + Send,
+{
+    /// Count the events pushed to `target` as if they'd been received by the
+    /// link, for when events are pushed directly rather than through the link
+    ///
+    pub fn bypass(self, target: Option<Box<Push<A> + Send>>) -> Option<Box<Push<A> + Send>> {
+        match target {
+            Some(t) => Some(Box::new(Bypass { metrics: self.metrics, target: t })),
+            None => None,
+        }
+    }
+
+    /// Push received events to `target` until `Event::Exit` is received
+    ///
+    /// Queues block the calling thread until then, unless a join on this
+    /// thread is pumping its parents, in which case the queue is handed to
+    /// the join and this returns immediately.  Mailboxes return immediately
+    /// and push events as the scheduler runs them.
+    ///
+    pub fn push_to(self, target: Option<Box<Push<A> + Send>>) {
+        let LinkRx { name, metrics, end } = self;
+        let mut target = target;
+
+        match end {
+            RxEnd::Queue(rx) => {
+                let wakeup = PUMPS.with(|pumps| {
+                    pumps.borrow().as_ref().map(|p| p.wakeup.clone())
+                });
+
+                match wakeup {
+                    Some(wakeup) => {
+                        debug!("SETUP: [{}] Link handing queue to join", name);
+                        *rx.0.watch.lock().unwrap() = Some(wakeup);
+
+                        let pump = Box::new(move || {
+                            match rx.try_recv() {
+                                Ok(event) => Some(receive(&name, &metrics, &mut target, Some(event))),
+                                Err(TryRecvError::Empty) => None,
+                                Err(TryRecvError::Disconnected) => Some(receive(&name, &metrics, &mut target, None)),
+                            }
+                        });
+                        PUMPS.with(|pumps| {
+                            if let Some(ref mut p) = *pumps.borrow_mut() {
+                                p.pumps.push(pump);
+                            }
+                        });
+                    },
+                    None => {
+                        debug!("SETUP: [{}] Link receiving", name);
+                        while receive(&name, &metrics, &mut target, rx.recv()) {}
+                    },
+                }
+            },
+            RxEnd::Mailbox(mailbox) => {
+                debug!("SETUP: [{}] Link installing mailbox target", name);
+                *mailbox.target.lock().unwrap() = target;
+            },
+        }
+    }
+}
+
+struct Bypass<A> {
+    metrics: NodeMetrics,
+    target: Box<Push<A> + Send>,
+}
+
+impl<A> Push<A> for Bypass<A> where
+    A: 'static + Send,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+        self.target.push(event);
+    }
+}
+
+// Pushes an event received by a link to `target`, returns false once
+// `Event::Exit` is received or `event` is None because the sending end has
+// gone
+fn receive<A>(name: &str, metrics: &NodeMetrics, target: &mut Option<Box<Push<A> + Send>>, event: Option<Event<A>>) -> bool {
+    if let Some(ref event) = event {
+        metrics.dequeued();
+        metrics.record(event);
+    }
+
+    match (event, target.as_mut()) {
+        (None, Some(t)) => {
+            info!("RUN: [{}] Link disconnected, exiting", name);
+            t.push(Event::Exit);
+            false
+        },
+        (Some(Event::Exit), Some(t)) => {
+            info!("RUN: [{}] Link received Exit, exiting", name);
+            t.push(Event::Exit);
+            false
+        },
+        (Some(event), Some(t)) => {
+            info!("RUN: [{}] Link received data, pushing", name);
+            t.push(event);
+            true
+        },
+        (None, None) => {
+            info!("RUN: [{}] Link disconnected with no target, exiting", name);
+            false
+        },
+        (Some(Event::Exit), None) => {
+            info!("RUN: [{}] Link received Exit with no target, exiting", name);
+            false
+        },
+        (Some(_), None) => {
+            // Just ensuring the queue is drained so we don't get memory leaks
+            info!("RUN: [{}] Link received data, but no target", name);
+            true
+        },
+    }
+}
+
+thread_local! {
+    // Set while a join is connecting its parents, to collect the queues
+    // their nodes receive from
+    static PUMPS: RefCell<Option<Pumps>> = const { RefCell::new(None) };
+}
+
+/// The queues of the parents of a join, which are received from by the
+/// join's thread rather than threads of their own
+///
+/// Each pump receives an event if one is waiting and pushes it through the
+/// parent's nodes, returning `None` if nothing was waiting or `Some(false)`
+/// once the queue has exited.  Parents are pumped in whatever order their
+/// events arrive, so a fork sending to several of them in a different order
+/// than the join reads them can't hold it up.
+///
+pub struct Pumps {
+    wakeup: Arc<Wakeup>,
+    pumps: Vec<Box<FnMut() -> Option<bool> + Send>>,
+}
+
+impl Pumps {
+    /// Start collecting the queues linked to on this thread, returns false
+    /// if they're already being collected by another join
+    ///
+    pub fn start() -> bool {
+        PUMPS.with(|pumps| {
+            let mut pumps = pumps.borrow_mut();
+            if pumps.is_some() { return false }
+
+            *pumps = Some(Pumps {
+                wakeup: Arc::new(Wakeup::new()),
+                pumps: Vec::new(),
+            });
+            true
+        })
+    }
+
+    /// Stop collecting queues, returning the ones collected
+    ///
+    pub fn take() -> Pumps {
+        PUMPS.with(|pumps| pumps.borrow_mut().take()).unwrap()
+    }
+
+    /// Pump events until `running` returns false, blocking the calling
+    /// thread while there are none waiting
+    ///
+    pub fn run<F>(self, mut running: F) where
+        F: FnMut() -> bool,
+    {
+        let Pumps { wakeup, mut pumps } = self;
+
+        while running() && !pumps.is_empty() {
+            let mut pumped = false;
+
+            let mut i = 0;
+            while i < pumps.len() {
+                match pumps[i]() {
+                    Some(true) => {
+                        pumped = true;
+                        i += 1;
+                    },
+                    Some(false) => {
+                        // Dropping the pump drops the queue's receiving end
+                        pumped = true;
+                        drop(pumps.remove(i));
+                    },
+                    None => i += 1,
+                }
+            }
+
+            if !pumped {
+                wakeup.wait();
+            }
+        }
+    }
+}
+
+// Wakes a thread waiting on several queues
+struct Wakeup {
+    woken: Mutex<bool>,
+    ready: Condvar,
+}
+
+impl Wakeup {
+    fn new() -> Wakeup {
+        Wakeup {
+            woken: Mutex::new(false),
+            ready: Condvar::new(),
+        }
+    }
+
+    fn wake(&self) {
+        *self.woken.lock().unwrap() = true;
+        self.ready.notify_one();
+    }
+
+    fn wait(&self) {
+        let mut woken = self.woken.lock().unwrap();
+        while !*woken {
+            woken = self.ready.wait(woken).unwrap();
+        }
+        *woken = false;
+    }
+}
+
+// The events waiting in a link
+//
+// When a lossy link is full it drops values rather than events: the dropped
+// value is replaced by `Event::Unchanged`, so nodes downstream still see one
+// event per global event and joins stay in step.  Consecutive unchanged
+// events are kept as a count, so only values take up room.
+struct Pending<A> {
+    slots: VecDeque<Slot<A>>,
+    // The number of events and values waiting
+    len: usize,
+    values: usize,
+}
+
+enum Slot<A> {
+    Changed(A),
+    Unchanged(usize),
+    Exit,
+}
+
+impl<A> Pending<A> {
+    fn new() -> Pending<A> {
+        Pending {
+            slots: VecDeque::new(),
+            len: 0,
+            values: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn pop(&mut self) -> Option<Event<A>> {
+        let slot = self.slots.pop_front()?;
+        self.len -= 1;
+
+        match slot {
+            Slot::Changed(a) => {
+                self.values -= 1;
+                Some(Event::Changed(a))
+            },
+            Slot::Unchanged(n) => {
+                if n > 1 {
+                    self.slots.push_front(Slot::Unchanged(n - 1));
+                }
+                Some(Event::Unchanged)
+            },
+            Slot::Exit => Some(Event::Exit),
+        }
+    }
+
+    // Adds `event` following `policy`, returns false if a value had to be
+    // dropped to keep within `capacity` values.  Lossy policies never drop
+    // unchanged events or `Event::Exit`
+    fn offer(&mut self, event: Event<A>, policy: BackpressurePolicy, capacity: usize) -> bool {
+        let full = self.values >= capacity;
+
+        match (event, policy) {
+            (Event::Exit, _) => {
+                self.push(Slot::Exit);
+                true
+            },
+            (Event::Unchanged, _) => {
+                self.push(Slot::Unchanged(1));
+                true
+            },
+            (Event::Changed(a), BackpressurePolicy::Block) => {
+                self.push(Slot::Changed(a));
+                true
+            },
+            (Event::Changed(a), BackpressurePolicy::DropNewest) => {
+                if full {
+                    self.push(Slot::Unchanged(1));
+                    return false
+                }
+
+                self.push(Slot::Changed(a));
+                true
+            },
+            (Event::Changed(a), _) => {
+                // With `Latest` there's only room for one value, so the
+                // oldest is also the one waiting
+                let dropped = full && self.drop_oldest();
+                self.push(Slot::Changed(a));
+                !dropped
+            },
+        }
+    }
+
+    fn push(&mut self, slot: Slot<A>) {
+        self.len += 1;
+
+        match slot {
+            Slot::Unchanged(n) => {
+                if let Some(&mut Slot::Unchanged(ref mut m)) = self.slots.back_mut() {
+                    *m += n;
+                    return
+                }
+            },
+            Slot::Changed(_) => self.values += 1,
+            Slot::Exit => {},
+        }
+
+        self.slots.push_back(slot);
+    }
+
+    // Replaces the oldest waiting value with an unchanged event, returns
+    // false if there were no values waiting
+    fn drop_oldest(&mut self) -> bool {
+        let i = match self.slots.iter().position(|slot| matches!(*slot, Slot::Changed(_))) {
+            Some(i) => i,
+            None => return false,
+        };
+        self.values -= 1;
+
+        // Merges with the unchanged events either side
+        let mut n = 1;
+        let mut start = i;
+        if let Some(&Slot::Unchanged(m)) = self.slots.get(i + 1) {
+            n += m;
+            self.slots.remove(i + 1);
+        }
+        if i > 0 {
+            if let Slot::Unchanged(m) = self.slots[i - 1] {
+                n += m;
+                self.slots.remove(i);
+                start = i - 1;
+            }
+        }
+
+        self.slots[start] = Slot::Unchanged(n);
+        true
+    }
+}
+
+// A bounded queue for links run by `Executor::ThreadPerNode`.  Blocking
+// queues hold up the sending end until there's room, lossy ones never do
+struct Queue<A> {
+    policy: BackpressurePolicy,
+    capacity: usize,
+    events: Mutex<Pending<A>>,
+    ready: Condvar,
+    space: Condvar,
+    // Woken as well as `ready` once a join is receiving from the queue
+    watch: Mutex<Option<Arc<Wakeup>>>,
+    // Set by whichever end goes first
+    closed: AtomicBool,
+}
+
+impl<A> Queue<A> {
+    fn new(policy: BackpressurePolicy, capacity: usize) -> Queue<A> {
+        Queue {
+            policy,
+            capacity,
+            events: Mutex::new(Pending::new()),
+            ready: Condvar::new(),
+            space: Condvar::new(),
+            watch: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    fn wake(&self) {
+        if let Some(ref wakeup) = *self.watch.lock().unwrap() {
+            wakeup.wake();
+        }
+    }
+
+    fn close(&self) {
+        let _events = self.events.lock().unwrap();
+        self.closed.store(true, Ordering::SeqCst);
+        self.ready.notify_all();
+        self.space.notify_all();
+        self.wake();
+    }
+}
+
+// Shared by clones of a `LinkTx`, closes the queue once they've all gone
+struct QueueTx<A>(Arc<Queue<A>>);
+
+impl<A> Drop for QueueTx<A> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+struct QueueRx<A>(Arc<Queue<A>>);
+
+impl<A> QueueRx<A> {
+    // Blocks until an event is waiting, returns None once the queue is empty
+    // and the sending end has gone
+    fn recv(&self) -> Option<Event<A>> {
+        let queue = &self.0;
+        let mut events = queue.events.lock().unwrap();
+
+        loop {
+            match events.pop() {
+                Some(event) => {
+                    queue.space.notify_all();
+                    return Some(event)
+                },
+                None => {
+                    if queue.closed.load(Ordering::SeqCst) { return None }
+                    events = queue.ready.wait(events).unwrap();
+                },
+            }
+        }
+    }
+
+    fn try_recv(&self) -> Result<Event<A>, TryRecvError> {
+        let queue = &self.0;
+        let mut events = queue.events.lock().unwrap();
+
+        match events.pop() {
+            Some(event) => {
+                queue.space.notify_all();
+                Ok(event)
+            },
+            None => {
+                if queue.closed.load(Ordering::SeqCst) {
+                    Err(TryRecvError::Disconnected)
+                } else {
+                    Err(TryRecvError::Empty)
+                }
+            },
+        }
+    }
+}
+
+impl<A> Drop for QueueRx<A> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// A queue of events waiting to be pushed to a node
+///
+/// Mailboxes are scheduled whenever an event is sent to them, and only run
+/// by one worker at a time, so their events are pushed in order.
+///
+pub struct Mailbox<A> {
+    name: String,
+    metrics: NodeMetrics,
+    scheduler: Scheduler,
+    policy: BackpressurePolicy,
+    capacity: usize,
+    queue: Mutex<Pending<A>>,
+    target: Mutex<Option<Box<Push<A> + Send>>>,
+    scheduled: AtomicBool,
+    closed: AtomicBool,
+    // Set while the mailbox holds `capacity` events or more
+    full: AtomicBool,
+}
+
+impl<A> Mailbox<A> {
+    fn new(scheduler: Scheduler, name: String, metrics: NodeMetrics, policy: BackpressurePolicy, capacity: usize) -> Mailbox<A> {
+        Mailbox {
+            name,
+            metrics,
+            scheduler,
+            policy,
+            capacity,
+            queue: Mutex::new(Pending::new()),
+            target: Mutex::new(None),
+            scheduled: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            full: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<A> Job for Mailbox<A> where
+    A: 'static + Send,
+{
+    fn run(&self) {
+        let mut target = self.target.lock().unwrap();
+
+        loop {
+            let event = {
+                let mut queue = self.queue.lock().unwrap();
+                let event = queue.pop();
+                if queue.len() < self.capacity && self.full.swap(false, Ordering::SeqCst) {
+                    self.scheduler.drained();
+                }
+                event
+            };
+
+            if let Some(ref event) = event {
+                self.metrics.dequeued();
+                self.metrics.record(event);
+            }
+
+            match event {
+                Some(Event::Exit) => {
+                    info!("RUN: [{}] Mailbox received Exit", self.name);
+                    if let Some(ref mut t) = *target {
+                        t.push(Event::Exit);
+                    }
+
+                    // Drop the target so anything it owns is released
+                    *target = None;
+                    self.closed.store(true, Ordering::SeqCst);
+                    self.scheduler.handled();
+                },
+                Some(event) => {
+                    info!("RUN: [{}] Mailbox received data, pushing", self.name);
+                    if let Some(ref mut t) = *target {
+                        t.push(event);
+                    }
+                    self.scheduler.handled();
+                },
+                None => {
+                    self.scheduled.store(false, Ordering::SeqCst);
+
+                    // An event may have been queued after the queue was found
+                    // empty but before it was marked as unscheduled, in which
+                    // case it's up to us to handle it
+                    if self.queue.lock().unwrap().is_empty() || self.scheduled.swap(true, Ordering::SeqCst) {
+                        return
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::{Join, Lockstep};
+
+/// The result of a `merge` or `merge_all` operation
+///
+pub struct MergeSignal<A> where
+    A: 'static + Send + Clone,
+{
+    config: Config,
+    parents: Vec<Box<Signal<A>>>,
+    initial: SignalType<A>,
+}
+
+impl<A> MergeSignal<A> where
+    A: 'static + Send + Clone,
+{
+    pub fn new(config: Config, parents: Vec<Box<Signal<A>>>) -> Self {
+        let initial = {
+            let first = match parents.first() {
+                Some(p) => p.initial().unwrap(),
+                None => panic!("merge requires at least one signal"),
+            };
+
+            let any_dynamic = parents.iter().any(|p| {
+                match p.initial() {
+                    SignalType::Constant(_) => false,
+                    SignalType::Dynamic(_) => true,
+                }
+            });
+
+            if any_dynamic {
+                SignalType::Dynamic(first)
+            } else {
+                SignalType::Constant(first)
+            }
+        };
+
+        MergeSignal {
+            config,
+            parents,
+            initial,
+        }
+    }
+}
+
+impl<A> Signal<A> for MergeSignal<A> where
+    A: 'static + Send + Clone,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<A> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("merge", self.config.name(), self.initial.is_constant(), self.parents.iter().map(|p| p.describe()).collect())
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        let inner = *self;
+        let MergeSignal {config, parents, initial: _} = inner;
+
+        let join = Join::new(&config, "merge");
+        let name = join.name().to_string();
+        let mut inputs = parents.into_iter()
+            .map(|p| join.input(p))
+            .collect::<Vec<Lockstep<A>>>();
+
+        join.run(target, move || {
+            let mut out = Event::Unchanged;
+
+            // Every input has to be read for each event, even once a change
+            // has been found, otherwise the inputs fall out of step
+            for input in inputs.iter_mut() {
+                match input.recv() {
+                    Some(Value::Changed(a)) => {
+                        match out {
+                            Event::Unchanged => {
+                                info!("RUN: [{}] Merge using changed value", name);
+                                out = Event::Changed(a);
+                            },
+                            _ => {
+                                info!("RUN: [{}] Merge dropping simultaneous change", name);
+                            },
+                        }
+                    },
+                    Some(Value::Unchanged(_)) => {},
+                    None => return None,
+                }
+            }
+
+            Some(out)
+        });
+    }
+}
+impl<A> SignalExt<A> for MergeSignal<A> where
+    A: 'static + Send + Clone,
+{}
+
+impl<A> Configurable for MergeSignal<A> where
+    A: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
@@ -2,9 +2,21 @@
 //!
 pub mod async;
 pub mod channel;
+pub mod drop_repeats;
+pub mod filter;
 pub mod fold;
 pub mod fork;
 pub mod input;
+pub mod join;
 pub mod lift;
 pub mod lift2;
+pub mod lift_all;
+pub mod liftn;
+pub mod link;
+pub mod merge;
+pub mod sample_on;
+pub mod sink;
+pub mod switch;
+pub mod try_fold;
+pub mod try_lift;
 pub mod value;
@@ -0,0 +1,96 @@
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::Join;
+
+/// The result of a `sample_on` operation
+///
+pub struct SampleOnSignal<A, T> where
+    A: 'static + Send + Clone,
+    T: 'static + Send + Clone,
+{
+    config: Config,
+    parent: Box<Signal<A>>,
+    trigger: Box<Signal<T>>,
+    initial: SignalType<A>,
+}
+
+impl<A, T> SampleOnSignal<A, T> where
+    A: 'static + Send + Clone,
+    T: 'static + Send + Clone,
+{
+    pub fn new(config: Config, parent: Box<Signal<A>>, trigger: Box<Signal<T>>) -> Self {
+        // Values are only emitted when the trigger changes, so a constant
+        // trigger means a constant output
+        let initial = match trigger.initial() {
+            SignalType::Constant(_) => SignalType::Constant(parent.initial().unwrap()),
+            SignalType::Dynamic(_) => SignalType::Dynamic(parent.initial().unwrap()),
+        };
+
+        SampleOnSignal {
+            config,
+            parent,
+            trigger,
+            initial,
+        }
+    }
+}
+
+impl<A, T> Signal<A> for SampleOnSignal<A, T> where
+    A: 'static + Send + Clone,
+    T: 'static + Send + Clone,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<A> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("sample_on", self.config.name(), self.initial.is_constant(), vec![self.parent.describe(), self.trigger.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        let inner = *self;
+        let SampleOnSignal {config, parent, trigger, initial: _} = inner;
+
+        let join = Join::new(&config, "sample_on");
+        let name = join.name().to_string();
+        let mut parent = join.input(parent);
+        let mut trigger = join.input(trigger);
+
+        join.run(target, move || {
+            let a = match parent.recv() {
+                Some(a) => a,
+                None => return None,
+            };
+
+            let out = match trigger.recv() {
+                Some(Value::Changed(_)) => {
+                    info!("RUN: [{}] SampleOn trigger changed, sampling", name);
+                    Event::Changed(a.into_inner())
+                },
+                Some(Value::Unchanged(_)) => {
+                    info!("RUN: [{}] SampleOn trigger unchanged", name);
+                    Event::Unchanged
+                },
+                None => return None,
+            };
+
+            Some(out)
+        });
+    }
+}
+impl<A, T> SignalExt<A> for SampleOnSignal<A, T> where
+    A: 'static + Send + Clone,
+    T: 'static + Send + Clone,
+{}
+
+impl<A, T> Configurable for SampleOnSignal<A, T> where
+    A: 'static + Send + Clone,
+    T: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
@@ -0,0 +1,101 @@
+use super::super::{Event, Signal, SignalType, Push, Run, Config, Node, NodeMetrics, NodeRole};
+
+// A Sink is created internally when Builder#sink or Builder#output is called.
+// It's the terminal node of a chain of transformations, and passes each
+// changed value to a function.  The function is dropped once the topology
+// exits, which closes any channel it owns.
+//
+pub struct Sink<F, A> where
+    F: 'static + Send + Fn(A),
+    A: 'static + Send + Clone,
+{
+    parent: Box<Signal<A>>,
+    f: F,
+}
+
+impl<F, A> Sink<F, A> where
+    F: 'static + Send + Fn(A),
+    A: 'static + Send + Clone,
+{
+    pub fn new(parent: Box<Signal<A>>, f: F) -> Sink<F, A> {
+        Sink {
+            parent,
+            f,
+        }
+    }
+}
+
+impl<F, A> Run for Sink<F, A> where
+    F: 'static + Send + Fn(A),
+    A: 'static + Send + Clone,
+{
+    fn run(self: Box<Self>) {
+        let inner = *self;
+        let Sink { parent, f } = inner;
+        let config = parent.config();
+
+        // Sinks are named after the signal they consume
+        let name = match config.name() {
+            Some(name) => format!("{} sink", name),
+            None => "sink".to_string(),
+        };
+
+        debug!("[{}] Sink::run", name);
+
+        match parent.initial() {
+            SignalType::Constant(a) => {
+                config.supervisor().guard("sink", || f(a));
+            },
+            SignalType::Dynamic(a) => {
+                config.supervisor().guard("sink", || f(a));
+                let metrics = config.metrics().register(&name, NodeRole::Node);
+                parent.push_to(Some(Box::new(SinkPusher { name, config, metrics, f: Some(f) })));
+            },
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        self.parent.config().name()
+    }
+
+    fn describe(&self) -> Node {
+        let parent = self.parent.describe();
+        Node::new("sink", None, parent.constant, vec![parent])
+    }
+
+    fn fuse(self: Box<Self>) -> Option<Box<Run>> {
+        Some(self)
+    }
+}
+
+struct SinkPusher<F> {
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    f: Option<F>,
+}
+
+impl<F, A> Push<A> for SinkPusher<F> where
+    F: 'static + Send + Fn(A),
+    A: 'static + Send + Clone,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
+        match event {
+            Event::Changed(a) => {
+                info!("RUN: [{}] SinkPusher handling Event::Changed", self.name);
+                if let Some(ref f) = self.f {
+                    self.metrics.time(|| self.config.supervisor().guard("sink", || f(a)));
+                }
+            },
+            Event::Unchanged => {
+                info!("RUN: [{}] SinkPusher handling Event::Unchanged", self.name);
+            },
+            Event::Exit => {
+                info!("RUN: [{}] SinkPusher handling Event::Exit", self.name);
+                self.f = None;
+            },
+        }
+    }
+}
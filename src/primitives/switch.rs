@@ -0,0 +1,142 @@
+use super::super::{Value, Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
+use super::join::{Join, Lockstep};
+
+/// The result of a `switch` operation
+///
+pub struct SwitchSignal<K, A> where
+    K: 'static + Send + Clone + PartialEq,
+    A: 'static + Send + Clone,
+{
+    config: Config,
+    key: Box<Signal<K>>,
+    branches: Vec<(K, Box<Signal<A>>)>,
+    selected: usize,
+    initial: SignalType<A>,
+}
+
+impl<K, A> SwitchSignal<K, A> where
+    K: 'static + Send + Clone + PartialEq,
+    A: 'static + Send + Clone,
+{
+    pub fn new(config: Config, key: Box<Signal<K>>, branches: Vec<(K, Box<Signal<A>>)>) -> Self {
+        let (k, mut constant) = match key.initial() {
+            SignalType::Constant(k) => (k, true),
+            SignalType::Dynamic(k) => (k, false),
+        };
+
+        let selected = match branches.iter().position(|(b, _)| *b == k) {
+            Some(i) => i,
+            None => panic!("switch has no branch for its initial key"),
+        };
+
+        for (_, branch) in branches.iter() {
+            constant = constant && branch.initial().is_constant();
+        }
+
+        let a = branches[selected].1.initial().unwrap();
+        let initial = if constant {
+            SignalType::Constant(a)
+        } else {
+            SignalType::Dynamic(a)
+        };
+
+        SwitchSignal {
+            config,
+            key,
+            branches,
+            selected,
+            initial,
+        }
+    }
+}
+
+impl<K, A> Signal<A> for SwitchSignal<K, A> where
+    K: 'static + Send + Clone + PartialEq,
+    A: 'static + Send + Clone,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<A> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        let mut parents = vec![self.key.describe()];
+        parents.extend(self.branches.iter().map(|(_, b)| b.describe()));
+
+        Node::new("switch", self.config.name(), self.initial.is_constant(), parents)
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<A> + Send>>) {
+        let inner = *self;
+        let SwitchSignal {config, key, branches, selected, initial: _} = inner;
+
+        let join = Join::new(&config, "switch");
+        let name = join.name().to_string();
+        let mut key = join.input(key);
+        let (keys, mut inputs): (Vec<K>, Vec<Lockstep<A>>) = branches.into_iter()
+            .map(|(k, b)| (k, join.input(b)))
+            .unzip();
+        let mut selected = selected;
+
+        join.run(target, move || {
+            let k = match key.recv() {
+                Some(k) => k,
+                None => return None,
+            };
+
+            // Every branch is read, selected or not, so each one stays in
+            // step with the global event
+            let mut values = Vec::with_capacity(inputs.len());
+            for input in inputs.iter_mut() {
+                match input.recv() {
+                    Some(v) => values.push(v),
+                    None => return None,
+                }
+            }
+
+            // Keys without a branch leave the selection as it was
+            let switched = match k {
+                Value::Changed(k) => {
+                    match keys.iter().position(|b| *b == k) {
+                        Some(i) if i != selected => {
+                            info!("RUN: [{}] Switch selecting branch {}", name, i);
+                            selected = i;
+                            true
+                        },
+                        _ => false,
+                    }
+                },
+                Value::Unchanged(_) => false,
+            };
+
+            let out = match values.swap_remove(selected) {
+                Value::Changed(a) => Event::Changed(a),
+                Value::Unchanged(a) => {
+                    if switched {
+                        Event::Changed(a)
+                    } else {
+                        Event::Unchanged
+                    }
+                },
+            };
+
+            Some(out)
+        });
+    }
+}
+impl<K, A> SignalExt<A> for SwitchSignal<K, A> where
+    K: 'static + Send + Clone + PartialEq,
+    A: 'static + Send + Clone,
+{}
+
+impl<K, A> Configurable for SwitchSignal<K, A> where
+    K: 'static + Send + Clone + PartialEq,
+    A: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
@@ -0,0 +1,190 @@
+use std::marker::*;
+use std::fmt::Display;
+
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole, PanicPolicy};
+
+/// The result of a `try_fold` operation
+///
+pub struct TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    config: Config,
+    parent: Box<Signal<A>>,
+    f: F,
+    // The error from the initial value, which is reported once the node runs
+    // so that it carries the node's name
+    failed: Option<String>,
+    state: SignalType<B>,
+    marker: PhantomData<E>,
+}
+
+impl<F, A, B, E> TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    pub fn new(config: Config, parent: Box<Signal<A>>, initial: B, f: F) -> Self {
+        let (a, constant) = match parent.initial() {
+            SignalType::Constant(a) => (a, true),
+            SignalType::Dynamic(a) => (a, false),
+        };
+
+        let (s, failed) = match f(initial.clone(), a) {
+            Ok(s) => (s, None),
+            Err(e) => (initial, Some(e.to_string())),
+        };
+
+        // Constant signals are never run, so their error is reported straight
+        // away
+        let failed = match failed {
+            Some(message) if constant => {
+                config.supervisor().fail(config.node_name("try_fold"), message);
+                None
+            },
+            failed => failed,
+        };
+
+        let state = if constant {
+            SignalType::Constant(s)
+        } else {
+            SignalType::Dynamic(s)
+        };
+
+        TryFoldSignal {
+            config,
+            parent,
+            f,
+            failed,
+            state,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<F, A, B, E> Signal<B> for TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<B> {
+        self.state.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("try_fold", self.config.name(), self.state.is_constant(), vec![self.parent.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
+        let inner = *self;
+        let TryFoldSignal { config, parent, f, failed, state, marker: _ } = inner;
+        let name = config.node_name("try_fold").to_string();
+
+        if let Some(message) = failed {
+            config.supervisor().fail(&name, message);
+        }
+
+        parent.push_to(
+            Some(
+                Box::new(
+                    TryFoldPusher {
+                        metrics: config.metrics().register(&name, NodeRole::Node),
+                        name,
+                        config,
+                        child: target,
+                        f,
+                        state: state.unwrap(),
+                        marker: PhantomData,
+                    }
+                )
+            )
+        );
+    }
+}
+impl<F, A, B, E> SignalExt<B> for TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{}
+
+impl<F, A, B, E> Configurable for TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
+struct TryFoldPusher<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<B> + Send>>,
+    f: F,
+    state: B,
+    marker: PhantomData<(A, E)>,
+}
+
+impl<F, A, B, E> Push<A> for TryFoldPusher<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
+        let out = match event {
+            Event::Changed(a) => {
+                debug!("[{}] TryFoldPusher handling Event::Changed", self.name);
+                let state = self.state.clone();
+                match self.metrics.time(|| self.config.supervisor().guard(&self.name, || (self.f)(state, a))) {
+                    Some(Ok(s)) => {
+                        self.state = s;
+                        Event::Changed(self.state.clone())
+                    },
+                    Some(Err(e)) => {
+                        // The accumulator keeps its previous value
+                        self.config.supervisor().fail(&self.name, e);
+                        Event::Unchanged
+                    },
+                    None => {
+                        match self.config.panic_policy {
+                            PanicPolicy::Restart => Event::Changed(self.state.clone()),
+                            _ => Event::Unchanged,
+                        }
+                    },
+                }
+            },
+            Event::Unchanged => {
+                debug!("[{}] TryFoldPusher handling Event::Unchanged", self.name);
+                Event::Unchanged
+            },
+            Event::Exit => {
+                debug!("[{}] TryFoldPusher handling Event::Exit", self.name);
+                Event::Exit
+            },
+        };
+
+        if let Some(ref mut c) = self.child {
+            c.push(out);
+        }
+    }
+}
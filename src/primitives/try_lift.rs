@@ -0,0 +1,181 @@
+use std::marker::*;
+use std::fmt::Display;
+
+use super::super::{Event, Signal, SignalExt, Configurable, SignalType, Push, Config, Node, NodeMetrics, NodeRole};
+
+/// The result of a `try_lift` operation
+///
+pub struct TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    config: Config,
+    parent: Box<Signal<A>>,
+    f: F,
+    // The error from the initial value, which is reported once the node runs
+    // so that it carries the node's name
+    failed: Option<String>,
+    initial: SignalType<B>,
+    marker: PhantomData<E>,
+}
+
+impl<F, A, B, E> TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    pub fn new(config: Config, parent: Box<Signal<A>>, default: B, f: F) -> Self {
+        let (a, constant) = match parent.initial() {
+            SignalType::Constant(a) => (a, true),
+            SignalType::Dynamic(a) => (a, false),
+        };
+
+        let (b, failed) = match f(a) {
+            Ok(b) => (b, None),
+            Err(e) => (default, Some(e.to_string())),
+        };
+
+        // Constant signals are never run, so their error is reported straight
+        // away
+        let failed = match failed {
+            Some(message) if constant => {
+                config.supervisor().fail(config.node_name("try_lift"), message);
+                None
+            },
+            failed => failed,
+        };
+
+        let initial = if constant {
+            SignalType::Constant(b)
+        } else {
+            SignalType::Dynamic(b)
+        };
+
+        TryLiftSignal {
+            config,
+            parent,
+            f,
+            failed,
+            initial,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<F, A, B, E> Signal<B> for TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    fn initial(&self) -> SignalType<B> {
+        self.initial.clone()
+    }
+
+    fn describe(&self) -> Node {
+        Node::new("try_lift", self.config.name(), self.initial.is_constant(), vec![self.parent.describe()])
+    }
+
+    fn push_to(self: Box<Self>, target: Option<Box<Push<B> + Send>>) {
+        let inner = *self;
+        let TryLiftSignal { config, parent, f, failed, initial: _, marker: _ } = inner;
+        let name = config.node_name("try_lift").to_string();
+
+        if let Some(message) = failed {
+            config.supervisor().fail(&name, message);
+        }
+
+        parent.push_to(
+            Some(
+                Box::new(
+                    TryLiftPusher {
+                        metrics: config.metrics().register(&name, NodeRole::Node),
+                        name,
+                        config,
+                        child: target,
+                        f,
+                        marker: PhantomData,
+                    }
+                )
+            )
+        );
+    }
+}
+impl<F, A, B, E> SignalExt<B> for TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{}
+
+impl<F, A, B, E> Configurable for TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
+
+struct TryLiftPusher<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    name: String,
+    config: Config,
+    metrics: NodeMetrics,
+    child: Option<Box<Push<B> + Send>>,
+    f: F,
+    marker: PhantomData<(A, E)>,
+}
+
+impl<F, A, B, E> Push<A> for TryLiftPusher<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    A: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+{
+    fn push(&mut self, event: Event<A>) {
+        self.metrics.record(&event);
+
+        let out = match event {
+            Event::Changed(a) => {
+                match self.metrics.time(|| self.config.supervisor().guard(&self.name, || (self.f)(a))) {
+                    Some(Ok(b)) => {
+                        info!("RUN: [{}] TryLiftPusher handling Event::Changed, succeeded", self.name);
+                        Event::Changed(b)
+                    },
+                    Some(Err(e)) => {
+                        info!("RUN: [{}] TryLiftPusher handling Event::Changed, failed", self.name);
+                        self.config.supervisor().fail(&self.name, e);
+                        Event::Unchanged
+                    },
+                    None => Event::Unchanged,
+                }
+            },
+            Event::Unchanged => {
+                info!("RUN: [{}] TryLiftPusher handling Event::Unchanged", self.name);
+                Event::Unchanged
+            },
+            Event::Exit => {
+                info!("RUN: [{}] TryLiftPusher handling Event::Exit", self.name);
+                Event::Exit
+            },
+        };
+
+        if let Some(ref mut t) = self.child {
+            t.push(out);
+        }
+    }
+}
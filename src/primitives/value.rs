@@ -1,4 +1,4 @@
-use super::super::{Signal, SignalExt, SignalType, Push, Config};
+use super::super::{Signal, SignalExt, Configurable, SignalType, Push, Config, Node};
 
 #[derive(Clone)]
 pub struct Value<A> where
@@ -27,10 +27,22 @@ impl<A> Signal<A> for Value<A> where
         SignalType::Constant(self.initial.clone())
     }
 
-    fn push_to(self: Box<Self>, _: Option<Box<Push<A>>>) {
+    fn describe(&self) -> Node {
+        Node::new("value", self.config.name(), true, vec![])
+    }
+
+    fn push_to(self: Box<Self>, _: Option<Box<Push<A> + Send>>) {
         panic!("Constant-typed signal asked to push - stack overflows ahoy!")
     }
 }
 impl<A> SignalExt<A> for Value<A> where
     A: 'static + Send + Clone,
 {}
+
+impl<A> Configurable for Value<A> where
+    A: 'static + Send + Clone,
+{
+    fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+}
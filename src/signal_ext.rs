@@ -1,7 +1,20 @@
-use super::{Signal, Builder, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::time::Duration;
+
+use super::{Signal, Configurable, Builder, Config, Value};
 use primitives::lift::LiftSignal;
 use primitives::lift2::{Lift2Signal};
+use primitives::liftn::{Lift3Signal, Lift4Signal, Lift5Signal, Lift6Signal, Lift7Signal, Lift8Signal};
 use primitives::fold::FoldSignal;
+use primitives::filter::FilterMapSignal;
+use primitives::drop_repeats::DropRepeatsSignal;
+use primitives::merge::MergeSignal;
+use primitives::sample_on::SampleOnSignal;
+use primitives::switch::SwitchSignal;
+use primitives::try_fold::TryFoldSignal;
+use primitives::try_lift::TryLiftSignal;
 use primitives::fork::Branch;
 
 /// Methods for manipulating signals
@@ -42,7 +55,7 @@ pub trait SignalExt<A>: Signal<A> + Sized where
     {
         self.init();
 
-        LiftSignal::new(self.config(), Box::new(self), f)
+        LiftSignal::new(child_config(&self), Box::new(self), f)
     }
 
     /// Combine two signals into an output signal
@@ -83,7 +96,372 @@ pub trait SignalExt<A>: Signal<A> + Sized where
         self.init();
         right.init();
 
-        Lift2Signal::new(self.config(), Box::new(self), Box::new(right), f)
+        Lift2Signal::new(child_config(&self), Box::new(self), Box::new(right), f)
+    }
+
+    /// Combine three signals into an output signal
+    ///
+    /// Like `lift2`, each input is wrapped in a `Value<T>` to distinguish
+    /// changed from unchanged data.  All the inputs are received from in a
+    /// single loop, which is cheaper than nesting `lift2` calls.  `lift4`
+    /// through `lift8` work the same way for more inputs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen(1 << 0, rx)
+    ///         .lift3(t.value(1 << 1), t.value(1 << 2), move |i, j, k| {
+    ///             out_tx.send(*i | *j | *k).unwrap()
+    ///         })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), (1 << 0) | (1 << 1) | (1 << 2));
+    ///
+    /// tx.send(1 << 3).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), (1 << 3) | (1 << 1) | (1 << 2));
+    /// ```
+    ///
+    fn lift3<F, S2, A2, S3, A3, B>(mut self, mut s2: S2, mut s3: S3, f: F) -> Lift3Signal<F, A, A2, A3, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+
+        Lift3Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), f)
+    }
+
+    /// Combine four signals into an output signal
+    ///
+    /// See `lift3`
+    ///
+    fn lift4<F, S2, A2, S3, A3, S4, A4, B>(mut self, mut s2: S2, mut s3: S3, mut s4: S4, f: F) -> Lift4Signal<F, A, A2, A3, A4, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    S4: 'static + Signal<A4>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>, Value<A4>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    A4: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+        s4.init();
+
+        Lift4Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), Box::new(s4), f)
+    }
+
+    /// Combine five signals into an output signal
+    ///
+    /// See `lift3`
+    ///
+    fn lift5<F, S2, A2, S3, A3, S4, A4, S5, A5, B>(mut self, mut s2: S2, mut s3: S3, mut s4: S4, mut s5: S5, f: F) -> Lift5Signal<F, A, A2, A3, A4, A5, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    S4: 'static + Signal<A4>,
+    S5: 'static + Signal<A5>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>, Value<A4>, Value<A5>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    A4: 'static + Send + Clone,
+    A5: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+        s4.init();
+        s5.init();
+
+        Lift5Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), Box::new(s4), Box::new(s5), f)
+    }
+
+    /// Combine six signals into an output signal
+    ///
+    /// See `lift3`
+    ///
+    // One argument per parent signal, like the smaller arities
+    #[allow(clippy::too_many_arguments)]
+    fn lift6<F, S2, A2, S3, A3, S4, A4, S5, A5, S6, A6, B>(mut self, mut s2: S2, mut s3: S3, mut s4: S4, mut s5: S5, mut s6: S6, f: F) -> Lift6Signal<F, A, A2, A3, A4, A5, A6, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    S4: 'static + Signal<A4>,
+    S5: 'static + Signal<A5>,
+    S6: 'static + Signal<A6>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>, Value<A4>, Value<A5>, Value<A6>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    A4: 'static + Send + Clone,
+    A5: 'static + Send + Clone,
+    A6: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+        s4.init();
+        s5.init();
+        s6.init();
+
+        Lift6Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), Box::new(s4), Box::new(s5), Box::new(s6), f)
+    }
+
+    /// Combine seven signals into an output signal
+    ///
+    /// See `lift3`
+    ///
+    // One argument per parent signal, like the smaller arities
+    #[allow(clippy::too_many_arguments)]
+    fn lift7<F, S2, A2, S3, A3, S4, A4, S5, A5, S6, A6, S7, A7, B>(mut self, mut s2: S2, mut s3: S3, mut s4: S4, mut s5: S5, mut s6: S6, mut s7: S7, f: F) -> Lift7Signal<F, A, A2, A3, A4, A5, A6, A7, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    S4: 'static + Signal<A4>,
+    S5: 'static + Signal<A5>,
+    S6: 'static + Signal<A6>,
+    S7: 'static + Signal<A7>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>, Value<A4>, Value<A5>, Value<A6>, Value<A7>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    A4: 'static + Send + Clone,
+    A5: 'static + Send + Clone,
+    A6: 'static + Send + Clone,
+    A7: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+        s4.init();
+        s5.init();
+        s6.init();
+        s7.init();
+
+        Lift7Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), Box::new(s4), Box::new(s5), Box::new(s6), Box::new(s7), f)
+    }
+
+    /// Combine eight signals into an output signal
+    ///
+    /// See `lift3`
+    ///
+    // One argument per parent signal, like the smaller arities
+    #[allow(clippy::too_many_arguments)]
+    fn lift8<F, S2, A2, S3, A3, S4, A4, S5, A5, S6, A6, S7, A7, S8, A8, B>(mut self, mut s2: S2, mut s3: S3, mut s4: S4, mut s5: S5, mut s6: S6, mut s7: S7, mut s8: S8, f: F) -> Lift8Signal<F, A, A2, A3, A4, A5, A6, A7, A8, B> where
+    S2: 'static + Signal<A2>,
+    S3: 'static + Signal<A3>,
+    S4: 'static + Signal<A4>,
+    S5: 'static + Signal<A5>,
+    S6: 'static + Signal<A6>,
+    S7: 'static + Signal<A7>,
+    S8: 'static + Signal<A8>,
+    F: 'static + Send + Fn(Value<A>, Value<A2>, Value<A3>, Value<A4>, Value<A5>, Value<A6>, Value<A7>, Value<A8>) -> B,
+    A2: 'static + Send + Clone,
+    A3: 'static + Send + Clone,
+    A4: 'static + Send + Clone,
+    A5: 'static + Send + Clone,
+    A6: 'static + Send + Clone,
+    A7: 'static + Send + Clone,
+    A8: 'static + Send + Clone,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+        s2.init();
+        s3.init();
+        s4.init();
+        s5.init();
+        s6.init();
+        s7.init();
+        s8.init();
+
+        Lift8Signal::new(child_config(&self), Box::new(self), Box::new(s2), Box::new(s3), Box::new(s4), Box::new(s5), Box::new(s6), Box::new(s7), Box::new(s8), f)
+    }
+
+    /// Combine two signals of the same type into a signal which emits
+    /// whichever value changed
+    ///
+    /// The initial value is taken from `self`.  If both signals change as
+    /// part of the same event (for instance because they share an upstream
+    /// signal), the value from `self` is used and the value from `other` is
+    /// discarded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (l_tx, l_rx) = sync_channel(0);
+    /// let (r_tx, r_rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen(1 << 0, l_rx)
+    ///         .merge(t.listen(1 << 1, r_rx))
+    ///         .lift(move |i| { out_tx.send(i).unwrap() })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 1 << 0);
+    ///
+    /// r_tx.send(1 << 2).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 1 << 2);
+    ///
+    /// l_tx.send(1 << 3).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 1 << 3);
+    /// ```
+    ///
+    fn merge<SB>(mut self, mut other: SB) -> MergeSignal<A> where
+    SB: 'static + Signal<A>,
+    {
+        self.init();
+        other.init();
+
+        MergeSignal::new(child_config(&self), vec![Box::new(self), Box::new(other)])
+    }
+
+    /// Emit the current value of `self` each time `trigger` changes
+    ///
+    /// Changes to `self` on their own are not emitted, but the most recent
+    /// value is always used when `trigger` changes, whether or not `self`
+    /// changed as part of the same event.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = sync_channel(0);
+    /// let (tick_tx, tick_rx) = sync_channel(0);
+    /// let (seen_tx, seen_rx) = channel();
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     let input = t.listen(0, in_rx);
+    ///
+    ///     input.clone()
+    ///         .lift(move |i| { seen_tx.send(i).unwrap() })
+    ///         .add_to(t);
+    ///
+    ///     input
+    ///         .sample_on(t.listen((), tick_rx))
+    ///         .lift(move |i| { out_tx.send(i).unwrap() })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial values
+    /// assert_eq!(seen_rx.recv().unwrap(), 0);
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// in_tx.send(1).unwrap();
+    /// in_tx.send(2).unwrap();
+    ///
+    /// // Separate inputs aren't ordered, so wait for the values to be seen
+    /// // before triggering
+    /// assert_eq!(seen_rx.recv().unwrap(), 1);
+    /// assert_eq!(seen_rx.recv().unwrap(), 2);
+    /// tick_tx.send(()).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 2);
+    ///
+    /// tick_tx.send(()).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 2);
+    /// ```
+    ///
+    fn sample_on<ST, T>(mut self, mut trigger: ST) -> SampleOnSignal<A, T> where
+    ST: 'static + Signal<T>,
+    T: 'static + Send + Clone,
+    {
+        self.init();
+        trigger.init();
+
+        SampleOnSignal::new(child_config(&self), Box::new(self), Box::new(trigger))
+    }
+
+    /// Forward the value of the branch whose key is the current value of
+    /// `self`
+    ///
+    /// The returned signal changes when the selected branch changes, or when
+    /// `self` changes to select a different branch, in which case it takes
+    /// that branch's latest value.  Values of `self` without a branch are
+    /// ignored, leaving the previous branch selected.
+    ///
+    /// Every branch is read on every event whether it's selected or not, so
+    /// branches keep running in lockstep with the rest of the topology and
+    /// switching back to one picks up where it has got to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no branch for the initial value of `self`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (_mode_tx, mode_rx) = channel();
+    /// let (_data_tx, data_rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut t = TestTopology::new(Default::default(), |t| {
+    ///     let mode = t.listen("raw", mode_rx);
+    ///     let data = t.listen(1usize, data_rx);
+    ///
+    ///     let mut views = HashMap::new();
+    ///     views.insert("raw", data.clone());
+    ///     views.insert("scaled", t.add(data.lift(|i| { i * 100 })));
+    ///
+    ///     out = Some(t.output(mode.switch(views)));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 1);
+    ///
+    /// t.push(1, 2usize);
+    /// assert_eq!(out_rx.try_recv().unwrap(), 2);
+    ///
+    /// // Switching emits the newly selected branch's latest value
+    /// t.push(0, "scaled");
+    /// assert_eq!(out_rx.try_recv().unwrap(), 200);
+    ///
+    /// // Unknown keys are ignored
+    /// t.push(0, "hex");
+    /// t.push(1, 3usize);
+    /// assert_eq!(out_rx.try_recv().unwrap(), 300);
+    /// ```
+    ///
+    fn switch<B>(mut self, branches: HashMap<A, Branch<B>>) -> SwitchSignal<A, B> where
+    A: Eq + Hash,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+
+        let branches = branches.into_iter().map(|(k, mut b)| {
+            b.init();
+            (k, Box::new(b) as Box<Signal<B>>)
+        }).collect();
+
+        SwitchSignal::new(child_config(&self), Box::new(self), branches)
     }
 
     /// Merge data from a signal into an accumulator and return a signal with
@@ -119,7 +497,308 @@ pub trait SignalExt<A>: Signal<A> + Sized where
     {
         self.init();
 
-        FoldSignal::new(self.config(), Box::new(self), initial, f)
+        FoldSignal::new(child_config(&self), Box::new(self), initial, f)
+    }
+
+    /// Transform an input signal with a function which can fail
+    ///
+    /// When `f` returns an error the signal keeps its previous value and is
+    /// treated as unchanged, and the error is reported through
+    /// `TopologyHandle::errors` as a `NodeErrorKind::Failed` error.  Unlike
+    /// panics these never stop the topology.  `default` is used as the
+    /// initial value if `f` fails on the initial value of `self`, in which
+    /// case the error is reported when the topology starts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let handle = spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(
+    ///         t.listen("1", in_rx)
+    ///             .try_lift(0, |s| { s.parse::<usize>() })
+    ///             .named("parse")
+    ///     ));
+    /// });
+    /// let out_rx = out.unwrap();
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 1);
+    ///
+    /// in_tx.send("nope").unwrap();
+    /// let error = handle.errors().recv().unwrap();
+    /// assert_eq!((error.node.as_str(), error.kind), ("parse", NodeErrorKind::Failed));
+    ///
+    /// in_tx.send("2").unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 2);
+    /// ```
+    ///
+    fn try_lift<F, B, E>(mut self, default: B, f: F) -> TryLiftSignal<F, A, B, E> where
+    F: 'static + Send + Fn(A) -> Result<B, E>,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+    {
+        self.init();
+
+        TryLiftSignal::new(child_config(&self), Box::new(self), default, f)
+    }
+
+    /// Merge data from a signal into an accumulator with a function which
+    /// can fail
+    ///
+    /// When `f` returns an error the accumulator keeps its previous value and
+    /// is treated as unchanged, and the error is reported through
+    /// `TopologyHandle::errors`, as with `try_lift`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let handle = spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(
+    ///         t.listen(0, in_rx)
+    ///             .try_fold(0u8, |sum, i| { sum.checked_add(i).ok_or("overflow") })
+    ///     ));
+    /// });
+    /// let out_rx = out.unwrap();
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// in_tx.send(200).unwrap();
+    /// in_tx.send(100).unwrap();
+    /// in_tx.send(50).unwrap();
+    ///
+    /// assert_eq!(handle.errors().recv().unwrap().to_string(), "try_fold failed: overflow");
+    /// assert_eq!(out_rx.recv().unwrap(), 200);
+    /// assert_eq!(out_rx.recv().unwrap(), 250);
+    /// ```
+    ///
+    fn try_fold<F, B, E>(mut self, initial: B, f: F) -> TryFoldSignal<F, A, B, E> where
+    F: 'static + Send + Fn(B, A) -> Result<B, E>,
+    B: 'static + Send + Clone,
+    E: 'static + Send + Display,
+    {
+        self.init();
+
+        TryFoldSignal::new(child_config(&self), Box::new(self), initial, f)
+    }
+
+    /// Return a signal with the last `n` values of `self`, oldest first
+    ///
+    /// The window slides by one value each time `self` changes.  It starts
+    /// out with the initial value of `self`, and holds fewer than `n` values
+    /// until `n` have been seen.  Aggregates such as moving averages can be
+    /// computed by lifting the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut t = TestTopology::new(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx)
+    ///         .window(3)
+    ///         .lift(|w| { w.iter().sum::<usize>() / w.len() })));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 0);
+    ///
+    /// for i in vec![3usize, 6, 9, 12] {
+    ///     t.push(0, i);
+    /// }
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<usize>>(), vec![1, 3, 6, 9]);
+    /// ```
+    ///
+    fn window(self, n: usize) -> Box<Signal<Vec<A>>> {
+        assert!(n > 0, "window size must be greater than 0");
+
+        let signal = self
+            .fold(VecDeque::with_capacity(n), move |mut window, a| {
+                if window.len() == n {
+                    window.pop_front();
+                }
+                window.push_back(a);
+                window
+            })
+            .lift(|window| { window.into_iter().collect() });
+
+        Box::new(signal)
+    }
+
+    /// Return a signal with batches of `n` values of `self`
+    ///
+    /// Batches don't overlap; each one is emitted once `n` values have been
+    /// seen since the last, and `self` changing without completing a batch
+    /// is treated as unchanged.  The initial value of `self` counts towards
+    /// the first batch, and the signal's initial value is empty unless that
+    /// completes it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut t = TestTopology::new(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx).tumbling(2)));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), vec![]);
+    ///
+    /// for i in 1..6usize {
+    ///     t.push(0, i);
+    /// }
+    /// assert_eq!(out_rx.try_iter().collect::<Vec<_>>(), vec![vec![0, 1], vec![2, 3], vec![4, 5]]);
+    /// ```
+    ///
+    fn tumbling(self, n: usize) -> Box<Signal<Vec<A>>> {
+        assert!(n > 0, "batch size must be greater than 0");
+
+        // The accumulator holds the batch being filled and, when a batch has
+        // just been completed, that batch
+        let signal = self
+            .fold((Vec::with_capacity(n), None), move |(mut batch, _), a| {
+                batch.push(a);
+                if batch.len() == n {
+                    (Vec::with_capacity(n), Some(batch))
+                } else {
+                    (batch, None)
+                }
+            })
+            .filter_map(Vec::new(), |(_, complete)| { complete });
+
+        Box::new(signal)
+    }
+
+    /// Return a signal with the values of `self` seen within the last `d`,
+    /// oldest first
+    ///
+    /// Values are timestamped with the `clock` of the signal's `Config`, the
+    /// same clock used by `Builder::every`.  The window is only updated when
+    /// `self` changes, so values older than `d` are dropped when the next
+    /// value arrives rather than as soon as they expire; the newest value is
+    /// always included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::Arc;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let clock = VirtualClock::new();
+    /// let mut config: Config = Default::default();
+    /// config.clock = Arc::new(clock.clone());
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut t = TestTopology::new(config, |t| {
+    ///     out = Some(t.output(t.listen(0usize, rx).window_for(Duration::from_secs(10))));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), vec![0]);
+    ///
+    /// clock.advance(Duration::from_secs(6));
+    /// t.push(0, 1usize);
+    /// assert_eq!(out_rx.try_recv().unwrap(), vec![0, 1]);
+    ///
+    /// clock.advance(Duration::from_secs(6));
+    /// t.push(0, 2usize);
+    /// assert_eq!(out_rx.try_recv().unwrap(), vec![1, 2]);
+    /// ```
+    ///
+    fn window_for(self, d: Duration) -> Box<Signal<Vec<A>>> {
+        let clock = self.config().clock.clone();
+
+        let signal = self
+            .fold(VecDeque::new(), move |mut window, a| {
+                let now = clock.now();
+                loop {
+                    match window.front() {
+                        Some(&(at, _)) if at + d <= now => {},
+                        _ => break,
+                    }
+                    window.pop_front();
+                }
+
+                window.push_back((now, a));
+                window
+            })
+            .lift(|window| { window.into_iter().map(|(_, a)| a).collect() });
+
+        Box::new(signal)
+    }
+
+    /// Name the signal's node
+    ///
+    /// Names are used for the node's threads, log lines, errors and in
+    /// `Builder::to_dot`.  They belong to a single node, so signals built
+    /// from a named signal start out unnamed.  Only signals which keep a
+    /// `Config` of their own, such as the built in signals, can be named.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let handle = spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(
+    ///         t.listen(1, in_rx)
+    ///             .lift(|i: usize| { 100 / i })
+    ///             .named("percent")
+    ///             .lift(|i| { i + 1 })
+    ///     ));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 101);
+    ///
+    /// in_tx.send(0).unwrap();
+    /// assert_eq!(handle.errors().recv().unwrap().node, "percent");
+    /// ```
+    ///
+    fn named(mut self, name: &str) -> Self where
+        Self: Configurable,
+    {
+        self.config_mut().set_name(Some(name.to_string()));
+        self
     }
 
     /// Sugar for `Builder::add`
@@ -134,6 +813,32 @@ pub trait SignalExt<A>: Signal<A> + Sized where
         builder.async(self)
     }
 
+    /// Sugar for `Builder::debounce`
+    ///
+    fn debounce(self, builder: &Builder, wait: Duration) -> Branch<A> {
+        builder.debounce(self, wait)
+    }
+
+    /// Sugar for `Builder::throttle`
+    ///
+    fn throttle(self, builder: &Builder, interval: Duration) -> Branch<A> {
+        builder.throttle(self, interval)
+    }
+
+    /// Sugar for `Builder::delay`
+    ///
+    fn delay(self, builder: &Builder, by: Duration) -> Branch<A> {
+        builder.delay(self, by)
+    }
+
+    /// Sugar for `Builder::sink`
+    ///
+    fn sink<F>(self, builder: &Builder, f: F) where
+    F: 'static + Send + Fn(A),
+    {
+        builder.sink(self, f)
+    }
+
     /// Alias of `lift`
     fn map<F, B>(self, f: F) -> LiftSignal<F, A, B> where
     F: 'static + Send + Fn(A) -> B,
@@ -233,7 +938,9 @@ pub trait SignalExt<A>: Signal<A> + Sized where
 
     /// Filter an input stream by a predicate function `F`.  
     ///
-    /// In this case 'filtered' is reflected by a value of `None`
+    /// In this case 'filtered' is reflected by a value of `None`, which is
+    /// still a change as far as downstream nodes are concerned.  To drop
+    /// filtered values entirely, see `keep_if`.
     ///
     /// # Example
     ///
@@ -276,6 +983,167 @@ pub trait SignalExt<A>: Signal<A> + Sized where
         )
     }
 
+    /// Only pass along values for which the predicate `F` returns `true`
+    ///
+    /// Rejected values are treated as if the signal hadn't changed, so nodes
+    /// downstream aren't recomputed and keep seeing the last accepted value.
+    /// `default` is used as the initial value if the initial value of `self`
+    /// is rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen(1, in_rx)
+    ///         .keep_if(0, |i| { i % 2 == 0 })
+    ///         .lift(move |i| { out_tx.send(i).unwrap(); })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value was rejected
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// in_tx.send(3).unwrap();
+    /// in_tx.send(4).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 4);
+    /// ```
+    ///
+    fn keep_if<F>(self, default: A, f: F) -> Box<Signal<A>> where
+    F: 'static + Send + Fn(&A) -> bool,
+    {
+        Box::new(
+            self.filter_map(default, move |i| {
+                if f(&i) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+        )
+    }
+
+    /// Drop values for which the predicate `F` returns `true`
+    ///
+    /// The inverse of `keep_if`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen(0, in_rx)
+    ///         .drop_if(0, |i| { i % 2 == 0 })
+    ///         .lift(move |i| { out_tx.send(i).unwrap(); })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// in_tx.send(2).unwrap();
+    /// in_tx.send(3).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 3);
+    /// ```
+    ///
+    fn drop_if<F>(self, default: A, f: F) -> Box<Signal<A>> where
+    F: 'static + Send + Fn(&A) -> bool,
+    {
+        self.keep_if(default, move |i| { !f(i) })
+    }
+
+    /// Transform values with `F`, dropping any for which it returns `None`
+    ///
+    /// Like `keep_if`, dropped values are treated as if the signal hadn't
+    /// changed.  `default` is used as the initial value if `F` returns `None`
+    /// for the initial value of `self`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen("1", in_rx)
+    ///         .filter_map(0, |s| { s.parse::<usize>().ok() })
+    ///         .lift(move |i| { out_tx.send(i).unwrap(); })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), 1);
+    ///
+    /// in_tx.send("nope").unwrap();
+    /// in_tx.send("2").unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 2);
+    /// ```
+    ///
+    fn filter_map<F, B>(mut self, default: B, f: F) -> FilterMapSignal<F, A, B> where
+    F: 'static + Send + Fn(A) -> Option<B>,
+    B: 'static + Send + Clone,
+    {
+        self.init();
+
+        FilterMapSignal::new(child_config(&self), Box::new(self), default, f)
+    }
+
+    /// Drop values which are equal to the previous value
+    ///
+    /// Repeated values are treated as if the signal hadn't changed, so nodes
+    /// downstream are only recomputed when the value actually changes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (in_tx, in_rx) = sync_channel(0);
+    /// let (out_tx, out_rx) = channel();
+    ///
+    /// spawn_topology(Default::default(), move |t| {
+    ///     t.listen(0, in_rx)
+    ///         .lift(|i| { i > 5 })
+    ///         .drop_repeats()
+    ///         .lift(move |b| { out_tx.send(b).unwrap(); })
+    ///         .add_to(t);
+    /// });
+    ///
+    /// // Initial value
+    /// assert_eq!(out_rx.recv().unwrap(), false);
+    ///
+    /// in_tx.send(1).unwrap();
+    /// in_tx.send(2).unwrap();
+    /// in_tx.send(6).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), true);
+    /// ```
+    ///
+    fn drop_repeats(mut self) -> DropRepeatsSignal<A> where
+    A: PartialEq,
+    {
+        self.init();
+
+        DropRepeatsSignal::new(child_config(&self), Box::new(self))
+    }
+
     /// Pass each value in a signal to `F` before sending it to an output 
     /// signal.  
     ///
@@ -290,3 +1158,11 @@ pub trait SignalExt<A>: Signal<A> + Sized where
         )
     }
 }
+
+// Config for a node built from `signal`, see `Config::child`
+fn child_config<A, S>(signal: &S) -> Config where
+    S: Signal<A>,
+    A: 'static + Send + Clone,
+{
+    signal.config().child()
+}
@@ -0,0 +1,135 @@
+use std::any::Any;
+use std::fmt;
+use std::panic;
+use std::sync::*;
+use std::sync::mpsc::*;
+
+/// An error raised by a node in the topology
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeError {
+    /// The node which raised the error
+    pub node: String,
+    /// The panic's message if it had one, or the error's `Display` output
+    pub message: String,
+    /// Whether the node panicked or returned an error
+    pub kind: NodeErrorKind,
+}
+
+impl fmt::Display for NodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.kind {
+            NodeErrorKind::Panic => write!(f, "{} panicked: {}", self.node, self.message),
+            NodeErrorKind::Failed => write!(f, "{} failed: {}", self.node, self.message),
+        }
+    }
+}
+
+/// How a node raised an error
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeErrorKind {
+    /// A function passed to the node panicked, which is handled according
+    /// to the topology's `panic_policy`
+    Panic,
+    /// A function passed to `try_lift` or `try_fold` returned an error.  The
+    /// node keeps its previous value and carries on
+    Failed,
+}
+
+/// Collects the errors raised by the nodes of a topology
+///
+/// Each topology gets its own supervisor when its `Builder` is created; the
+/// errors it collects are exposed by `TopologyHandle::errors`.
+///
+#[derive(Clone)]
+pub struct Supervisor {
+    tx: Sender<NodeError>,
+    rx: Arc<Mutex<Option<Receiver<NodeError>>>>,
+}
+
+impl Supervisor {
+    /// Create a new supervisor
+    ///
+    pub fn new() -> Supervisor {
+        let (tx, rx) = channel();
+
+        Supervisor {
+            tx,
+            rx: Arc::new(Mutex::new(Some(rx))),
+        }
+    }
+
+    /// Take the receiving end of the supervisor, this can only be done once
+    ///
+    pub fn take_errors(&self) -> Option<Receiver<NodeError>> {
+        self.rx.lock().unwrap().take()
+    }
+
+    /// Report a node error
+    ///
+    pub fn report(&self, error: NodeError) {
+        error!("RUN: {}", error);
+
+        // Nothing to do if nobody is listening
+        let _ = self.tx.send(error);
+    }
+
+    /// Report an error returned by a function passed to node `node`
+    ///
+    pub fn fail<E>(&self, node: &str, error: E) where
+        E: fmt::Display,
+    {
+        let error = NodeError {
+            node: node.to_string(),
+            message: error.to_string(),
+            kind: NodeErrorKind::Failed,
+        };
+        warn!("RUN: {}", error);
+
+        // Nothing to do if nobody is listening
+        let _ = self.tx.send(error);
+    }
+
+    /// Run `f`, returning `None` and reporting the error if it panics
+    ///
+    pub fn guard<F, R>(&self, node: &str, f: F) -> Option<R> where
+        F: FnOnce() -> R,
+    {
+        match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                self.report(NodeError {
+                    node: node.to_string(),
+                    message: panic_message(&e),
+                    kind: NodeErrorKind::Panic,
+                });
+                None
+            },
+        }
+    }
+}
+
+impl Default for Supervisor {
+    fn default() -> Supervisor {
+        Supervisor::new()
+    }
+}
+
+impl fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "Supervisor")
+    }
+}
+
+fn panic_message(e: &Box<Any + Send>) -> String {
+    match e.downcast_ref::<&'static str>() {
+        Some(s) => s.to_string(),
+        None => {
+            match e.downcast_ref::<String>() {
+                Some(s) => s.clone(),
+                None => "Box<Any>".to_string(),
+            }
+        },
+    }
+}
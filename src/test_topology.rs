@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::*;
+use std::sync::mpsc::*;
+
+use super::{Builder, Config};
+use config::PanicPolicy;
+use executor::{Executor, Scheduler, InputSet};
+use metrics::{Metrics, NodeStats};
+use supervisor::{NodeError, NodeErrorKind};
+use topology::fuse;
+use primitives::input::{Dispatch, Poll};
+
+/// A topology which runs on the calling thread, one event at a time
+///
+/// `TestTopology` is built the same way as `spawn_topology`, but doesn't
+/// start any threads.  Instead, data is pushed into the topology's inputs
+/// with `push`, which only returns once every node has handled the resulting
+/// event, so outputs can be checked with `try_recv`.
+///
+/// Inputs are numbered in the order they were created, starting from 0.
+/// This includes the inputs created by `async`, `every` and the `ack_*`
+/// signals.  Data sent to an input's channel, including the results of
+/// `async`, is only handled when `step` is called.
+///
+/// # Example
+///
+/// ```
+/// use std::default::Default;
+/// use std::sync::mpsc::*;
+/// use cfrp::*;
+///
+/// let (_tx, rx) = channel();
+/// let mut out = None;
+///
+/// let mut t = TestTopology::new(Default::default(), |t| {
+///     out = Some(t.output(t.listen(0usize, rx).fold(0, |sum, i| { sum + i })));
+/// });
+/// let out_rx = out.unwrap();
+///
+/// // Initial value
+/// assert_eq!(out_rx.try_recv().unwrap(), 0);
+///
+/// t.push(0, 1usize);
+/// assert_eq!(out_rx.try_recv().unwrap(), 1);
+///
+/// t.push(0, 2usize);
+/// assert_eq!(out_rx.try_recv().unwrap(), 3);
+/// ```
+///
+pub struct TestTopology {
+    scheduler: Scheduler,
+    panic_policy: PanicPolicy,
+    inputs: InputSet,
+    no_ops: Dispatch,
+    stopped: AtomicBool,
+    node_errors: Option<Receiver<NodeError>>,
+    errors_tx: Sender<NodeError>,
+    errors_rx: Receiver<NodeError>,
+    metrics: Metrics,
+}
+
+impl TestTopology {
+    /// Build a topology with `f`
+    ///
+    /// `config.executor` is ignored, nodes are always run on the calling
+    /// thread.
+    ///
+    pub fn new<F>(config: Config, f: F) -> TestTopology where
+        F: FnOnce(&Builder),
+    {
+        let mut config = config;
+        config.executor = Executor::Cooperative;
+
+        let builder = Builder::new(config);
+        f(&builder);
+
+        let config = builder.config();
+        let inputs = builder.inputs.into_inner();
+        let runners = fuse(builder.runners.into_inner());
+        let no_ops = Dispatch::new(&inputs, &runners);
+
+        // Connects each runner's nodes to the scheduler
+        for runner in runners.into_iter() {
+            runner.run();
+        }
+
+        let (errors_tx, errors_rx) = channel();
+        let mut topology = TestTopology {
+            scheduler: config.scheduler().clone(),
+            panic_policy: config.panic_policy,
+            inputs: InputSet::new(inputs),
+            no_ops,
+            stopped: AtomicBool::new(false),
+            node_errors: config.supervisor().take_errors(),
+            errors_tx,
+            errors_rx,
+            metrics: config.metrics().clone(),
+        };
+
+        // Errors may have been raised while computing initial values
+        topology.settle();
+        topology
+    }
+
+    /// Push `a` into the input numbered `input` and handle the resulting
+    /// event
+    ///
+    /// Data pushed after the topology has been stopped is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no input numbered `input`, or if it doesn't receive
+    /// data of type `A`
+    ///
+    pub fn push<A>(&mut self, input: usize, a: A) where
+        A: 'static + Send,
+    {
+        self.inputs.push(input, Box::new(a), &self.no_ops, &self.stopped);
+        self.settle();
+    }
+
+    /// Handle one event from data which has been sent to the topology's
+    /// inputs, returns false if there wasn't any
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::Default;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (_tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut t = TestTopology::new(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(1usize, rx).lift(|i| { i * 10 }).async(t)));
+    /// });
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.try_recv().unwrap(), 10);
+    ///
+    /// t.push(0, 2usize);
+    ///
+    /// // The async result is handled as a separate event
+    /// assert!(out_rx.try_recv().is_err());
+    /// assert!(t.step());
+    /// assert_eq!(out_rx.try_recv().unwrap(), 20);
+    /// assert!(!t.step());
+    /// ```
+    ///
+    pub fn step(&mut self) -> bool {
+        let polled = matches!(self.inputs.poll(&self.no_ops, &self.stopped), Poll::Ready);
+        self.settle();
+        polled
+    }
+
+    /// Stop the topology
+    ///
+    /// `Event::Exit` is handled by every node before this returns, so
+    /// outputs are closed.  Topologies are also stopped when dropped.
+    ///
+    pub fn stop(&mut self) {
+        self.no_ops.stop(&self.stopped);
+        self.settle();
+    }
+
+    /// Errors raised by the topology's nodes
+    ///
+    /// What happens to the topology after a panic depends on the
+    /// `panic_policy` it was configured with.  Errors returned by the
+    /// functions passed to `try_lift` and `try_fold` are only reported.
+    ///
+    pub fn errors(&self) -> &Receiver<NodeError> {
+        &self.errors_rx
+    }
+
+    /// A snapshot of the counters kept by each node, see
+    /// `TopologyHandle::metrics`
+    ///
+    pub fn metrics(&self) -> BTreeMap<usize, NodeStats> {
+        self.metrics.snapshot()
+    }
+
+    // Runs nodes until every queued event has been handled, applying the
+    // panic policy to any errors they raise
+    fn settle(&mut self) {
+        loop {
+            self.scheduler.run_queued();
+
+            let error = match self.node_errors {
+                Some(ref node_errors) => node_errors.try_recv().ok(),
+                None => None,
+            };
+
+            match error {
+                Some(error) => {
+                    let panicked = error.kind == NodeErrorKind::Panic;
+                    let _ = self.errors_tx.send(error);
+
+                    if panicked && self.panic_policy == PanicPolicy::Shutdown {
+                        self.no_ops.stop(&self.stopped);
+                    }
+                },
+                None => return,
+            }
+        }
+    }
+}
+
+impl Drop for TestTopology {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
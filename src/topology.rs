@@ -1,13 +1,25 @@
+use std::collections::BTreeMap;
+use std::panic;
 use std::sync::*;
+use std::sync::atomic::*;
+use std::sync::mpsc::*;
 use std::thread;
+use std::time::{Duration, Instant};
 
-use super::Run;
-use primitives::input::{RunInput, NoOp};
+use super::{Signal, Run, Config, Builder, Node};
+use config::PanicPolicy;
+use metrics::{Metrics, NodeStats};
+use executor::{Executor, InputSet};
+use supervisor::{Supervisor, NodeError, NodeErrorKind};
+use primitives::input::{RunInput, Dispatch};
+use primitives::channel::Channel;
+use primitives::fork::Branch;
 
 
 /// `Topology<T>` describes a data flow and controls its execution
 ///
 pub struct Topology {
+    config: Config,
     inputs: Vec<Box<RunInput>>,
     runners: Vec<Box<Run>>,
 }
@@ -15,35 +27,229 @@ pub struct Topology {
 impl Topology {
     /// Create a new topology
     ///
+    /// Errors raised by the topology's nodes are reported to the supervisor
+    /// of the `Config` the nodes were built with, so prefer `Builder::build`
+    /// to ensure they're exposed by `TopologyHandle::errors`.
+    ///
     pub fn new(inputs: Vec<Box<RunInput>>, runners: Vec<Box<Run>>) -> Self {
-        Topology { inputs: inputs, runners: runners }
+        Topology::with_config(Default::default(), inputs, runners)
+    }
+
+    /// Create a new topology, supervised according to `config`
+    ///
+    pub fn with_config(config: Config, inputs: Vec<Box<RunInput>>, runners: Vec<Box<Run>>) -> Self {
+        Topology { config, inputs, runners }
     }
 
     /// Run the topology
     ///
     pub fn run(self) -> TopologyHandle {
         info!("----> TOPOLOGY STARTING");
-        let Topology {inputs, runners} = self;
+        let Topology {config, inputs, runners} = self;
+        let runners = fuse(runners);
 
-        for runner in runners.into_iter() {
-            thread::spawn(move || {
-                runner.run();
-            });
+        let (done_tx, done_rx) = channel();
+        let mut nodes = Vec::new();
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let no_ops = Arc::new(Dispatch::new(&inputs, &runners));
+
+        match config.executor {
+            Executor::ThreadPerNode => {
+                for (idx, runner) in runners.into_iter().enumerate() {
+                    // Runners run everything upstream of them, so are named
+                    // after the signal they run
+                    let name = runner.name().unwrap_or_else(|| format!("runner {}", idx));
+                    let thread_name = name.clone();
+                    let supervisor = config.supervisor().clone();
+                    let done = Done { idx: nodes.len(), tx: done_tx.clone() };
+                    let handle = spawn(name.clone(), move || {
+                        let _done = done;
+                        supervise(&supervisor, thread_name, move || runner.run());
+                    });
+
+                    nodes.push(NodeThread { name, handle: Some(handle) });
+                }
+
+                for (idx, input) in inputs.into_iter().enumerate() {
+                    let name = format!("input {}", idx);
+                    let supervisor = config.supervisor().clone();
+                    let no_ops_i = no_ops.clone();
+                    let stopped_i = stopped.clone();
+                    let done = Done { idx: nodes.len(), tx: done_tx.clone() };
+                    let handle = spawn(name.clone(), move || {
+                        let _done = done;
+                        supervise(&supervisor, name, move || input.run(idx, no_ops_i, stopped_i));
+                    });
+
+                    nodes.push(NodeThread { name: format!("input {}", idx), handle: Some(handle) });
+                }
+            },
+            executor => {
+                // Without threads of their own, runners only connect their
+                // nodes to the scheduler and return, so this is done before
+                // any data is read from the inputs
+                for (idx, runner) in runners.into_iter().enumerate() {
+                    supervise(config.supervisor(), format!("runner {}", idx), move || runner.run());
+                }
+
+                let mut inputs = inputs;
+                for input in inputs.iter_mut() {
+                    input.wake_with(config.scheduler());
+                }
+
+                let inputs = Arc::new(Mutex::new(InputSet::new(inputs)));
+                for idx in 0..executor.workers().unwrap_or(1) {
+                    let name = format!("worker {}", idx);
+                    let supervisor = config.supervisor().clone();
+                    let scheduler = config.scheduler().clone();
+                    let inputs_w = inputs.clone();
+                    let no_ops_w = no_ops.clone();
+                    let stopped_w = stopped.clone();
+                    let done = Done { idx: nodes.len(), tx: done_tx.clone() };
+                    let handle = spawn(name.clone(), move || {
+                        let _done = done;
+                        supervise(&supervisor, name, move || scheduler.work(executor, &inputs_w, &no_ops_w, &stopped_w));
+                    });
+
+                    nodes.push(NodeThread { name: format!("worker {}", idx), handle: Some(handle) });
+                }
+            },
         }
 
-        let no_ops = Arc::new(Mutex::new(inputs.iter().map(|i| i.boxed_no_op()).collect::<Vec<Box<NoOp>>>()));
-        let term_txs = inputs.iter().map(|i| i.boxed_no_op()).collect::<Vec<Box<NoOp>>>();
-        for (idx, input) in inputs.into_iter().enumerate() {
-            let no_ops_i = no_ops.clone();
-            thread::spawn(move || {
-                input.run(idx, no_ops_i);
-            });
+        // Forwards node errors to the handle, stopping the topology if needed.
+        // Exits once every node (and so every copy of the supervisor) is gone.
+        let (errors_tx, errors_rx) = channel();
+        match config.supervisor().take_errors() {
+            Some(node_errors) => {
+                let policy = config.panic_policy;
+                let no_ops_s = no_ops.clone();
+                let stopped_s = stopped.clone();
+                let scheduler = config.scheduler().clone();
+
+                spawn("supervisor".to_string(), move || {
+                    for error in node_errors.iter() {
+                        // Only panics are subject to the panic policy
+                        let panicked = error.kind == NodeErrorKind::Panic;
+                        let _ = errors_tx.send(error);
+
+                        if panicked && policy == PanicPolicy::Shutdown {
+                            info!("----> TOPOLOGY SHUTTING DOWN AFTER ERROR");
+                            no_ops_s.stop(&stopped_s);
+                            scheduler.wake();
+                        }
+                    }
+                });
+            },
+            None => {
+                info!("----> TOPOLOGY SUPERVISOR ALREADY TAKEN, ERRORS WILL NOT BE REPORTED");
+            },
         }
 
         info!("----> TOPOLOGY RUNNING...");
 
         TopologyHandle {
-            term_txs: term_txs,
+            config: config.clone(),
+            no_ops,
+            stopped,
+            nodes,
+            done_rx,
+            errors_rx,
+            metrics: config.metrics().clone(),
+            drop_policy: DropPolicy::Detach,
+        }
+    }
+}
+
+// Gives each runner the chance to hand its work to another runner, returns
+// the runners which are left to run
+//
+pub fn fuse(runners: Vec<Box<Run>>) -> Vec<Box<Run>> {
+    runners.into_iter().filter_map(|r| r.fuse()).collect()
+}
+
+// Spawns a thread named `name`, so it can be told apart in logs & debuggers
+//
+pub fn spawn<F>(name: String, f: F) -> thread::JoinHandle<()> where
+    F: 'static + Send + FnOnce(),
+{
+    thread::Builder::new().name(name).spawn(f).unwrap()
+}
+
+// Runs a node's thread, reporting a panic if the node itself doesn't catch it.
+// The node is gone at that point, so the topology can't keep running
+// consistently whatever the panic policy is.
+//
+fn supervise<F>(supervisor: &Supervisor, name: String, f: F) where
+    F: FnOnce(),
+{
+    match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(_) => {},
+        Err(_) => {
+            supervisor.report(NodeError {
+                node: name,
+                message: "node thread panicked".to_string(),
+                kind: NodeErrorKind::Panic,
+            });
+        },
+    }
+}
+
+// Reports a node's thread as finished when it's dropped, which happens even
+// if the thread panics
+//
+struct Done {
+    idx: usize,
+    tx: Sender<usize>,
+}
+
+impl Drop for Done {
+    fn drop(&mut self) {
+        let _ = self.tx.send(self.idx);
+    }
+}
+
+/// What a `TopologyHandle` does to its topology when it's dropped
+///
+/// The default is `Detach`, which leaves the topology running; this allows
+/// the handle returned by `spawn_topology` to be ignored.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DropPolicy {
+    /// Leave the topology running
+    Detach,
+    /// Stop the topology without waiting for it to exit
+    Stop,
+    /// Stop the topology and wait up to the given duration for it to exit
+    StopAndJoin(Duration),
+}
+
+/// A subgraph attached to a running topology with `TopologyHandle::attach`
+///
+/// The subgraph keeps running until it's passed to `TopologyHandle::detach`
+/// or the topology stops.
+///
+pub struct Attachment {
+    // Sends `Event::Exit` to the subgraph
+    exit: Box<FnMut() + Send>,
+    nodes: Vec<(String, thread::JoinHandle<()>)>,
+}
+
+// A thread run by a topology
+struct NodeThread {
+    name: String,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl NodeThread {
+    // True until the thread has been joined
+    fn running(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    fn join(&mut self) {
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
         }
     }
 }
@@ -51,15 +257,333 @@ impl Topology {
 /// For explicitly terminating a running topology
 ///
 pub struct TopologyHandle {
-    term_txs: Vec<Box<NoOp>>,
+    // Keeps a copy of the topology's supervisor, so its errors are still
+    // forwarded while subgraphs can be attached
+    config: Config,
+    no_ops: Arc<Dispatch>,
+    stopped: Arc<AtomicBool>,
+    nodes: Vec<NodeThread>,
+    done_rx: Receiver<usize>,
+    errors_rx: Receiver<NodeError>,
+    metrics: Metrics,
+    drop_policy: DropPolicy,
 }
 
-// NOTE: Drop?  seems to kill tests for some reason, maybe because not capturing
 impl TopologyHandle {
+    /// Stop the topology
+    ///
+    /// `Event::Exit` is sent to every input, after any events currently being
+    /// dispatched.  Data which hasn't been received by an input yet is
+    /// discarded.  This returns immediately, use `join` or `stop_and_join` to
+    /// wait for the topology to finish processing.
+    ///
     pub fn stop(&mut self) {
-        for tx in self.term_txs.iter() {
-            tx.send_exit();
+        self.no_ops.stop(&self.stopped);
+        self.config.scheduler().wake();
+        debug!("----> TOPOLOGY STOPPED");
+    }
+
+    /// Errors raised by the topology's nodes
+    ///
+    /// What happens to the topology after a panic depends on the
+    /// `panic_policy` it was configured with.  Errors returned by the
+    /// functions passed to `try_lift` and `try_fold` are only reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    ///
+    /// let mut handle = spawn_topology(Default::default(), |t| {
+    ///     t.add(t.listen(1, rx).lift(|i: usize| { 10 / i }));
+    /// });
+    ///
+    /// tx.send(0).unwrap();
+    ///
+    /// let error = handle.errors().recv().unwrap();
+    /// assert_eq!(error.node, "lift");
+    ///
+    /// // The default policy is to stop the topology
+    /// handle.join();
+    /// ```
+    ///
+    pub fn errors(&self) -> &Receiver<NodeError> {
+        &self.errors_rx
+    }
+
+    /// A snapshot of the counters kept by each node, keyed by node id
+    ///
+    /// Nodes count the events they handle and the time spent in the
+    /// functions passed to them.  Channels and join inputs also count the
+    /// events waiting in them, which shows where a topology is backing up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let handle = spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(0, rx).lift(|i| { i + 1 }).named("plus one")));
+    /// });
+    /// let out_rx = out.unwrap();
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 1);
+    /// assert_eq!(out_rx.recv().unwrap(), 2);
+    ///
+    /// let metrics = handle.metrics();
+    /// let lift = metrics.values()
+    ///     .find(|n| n.name == "plus one" && n.role == NodeRole::Node)
+    ///     .unwrap();
+    /// assert_eq!(lift.changed, 1);
+    /// ```
+    ///
+    pub fn metrics(&self) -> BTreeMap<usize, NodeStats> {
+        self.metrics.snapshot()
+    }
+
+    /// Block until every node in the topology has exited
+    ///
+    /// This only returns once the topology has been stopped, either with
+    /// `stop` or because one of its inputs was disconnected.
+    ///
+    pub fn join(&mut self) {
+        loop {
+            if !self.nodes.iter().any(|n| n.running()) { break }
+
+            match self.done_rx.recv() {
+                Ok(idx) => self.nodes[idx].join(),
+                Err(_) => break,
+            }
+        }
+        debug!("----> TOPOLOGY JOINED");
+    }
+
+    /// Stop the topology and wait up to `timeout` for every node to exit
+    ///
+    /// Returns the names of any nodes which were still running after
+    /// `timeout` elapsed, including inputs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use std::time::Duration;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let mut out = None;
+    ///
+    /// let mut handle = spawn_topology(Default::default(), |t| {
+    ///     out = Some(t.output(t.listen(0, rx)));
+    /// });
+    ///
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 0);
+    ///
+    /// tx.send(1).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 1);
+    ///
+    /// assert!(handle.stop_and_join(Duration::from_secs(1)).is_ok());
+    ///
+    /// // Once the topology has exited, outputs are closed
+    /// assert!(out_rx.recv().is_err());
+    /// ```
+    ///
+    pub fn stop_and_join(&mut self, timeout: Duration) -> Result<(), Vec<String>> {
+        self.stop();
+
+        let deadline = Instant::now() + timeout;
+        while self.nodes.iter().any(|n| n.running()) {
+            let now = Instant::now();
+            if now >= deadline { break }
+
+            match self.done_rx.recv_timeout(deadline - now) {
+                Ok(idx) => self.nodes[idx].join(),
+                Err(_) => break,
+            }
+        }
+
+        let failed = self.nodes.iter()
+            .filter(|n| n.running())
+            .map(|n| n.name.clone())
+            .collect::<Vec<String>>();
+
+        if failed.is_empty() {
+            debug!("----> TOPOLOGY JOINED");
+            Ok(())
+        } else {
+            info!("----> TOPOLOGY FAILED TO JOIN: {:?}", failed);
+            Err(failed)
+        }
+    }
+
+    /// Attach a subgraph built by `f` to `branch` while the topology is
+    /// running
+    ///
+    /// `branch` must be a `Branch` of this topology which was kept when the
+    /// topology was built; its fork keeps sending events while it's held,
+    /// even if nothing else consumes it.  `f` is passed a builder and a new
+    /// `Branch` with the same values, and builds the subgraph from that
+    /// `Branch` alone: it can't create inputs or read other branches of the
+    /// topology.
+    ///
+    /// The new `Branch` starts with the latest value of `branch`, then
+    /// receives every event handled by `branch`'s fork after that value.
+    /// The fork is paused while the subgraph is connected, so no event is
+    /// half seen.  Other signals built from `branch` don't wait for the
+    /// subgraph, which gets its own link from the fork.
+    ///
+    /// Returns None without calling `f` if the topology has stopped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` creates an input or reads a branch of the running
+    /// topology other than the one it's passed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::default::*;
+    /// use std::sync::mpsc::*;
+    /// use cfrp::*;
+    ///
+    /// let (tx, rx) = channel();
+    /// let mut built = None;
+    ///
+    /// let mut handle = spawn_topology(Default::default(), |t| {
+    ///     let total = t.add(t.listen(0, rx).fold(0, |sum, i| { sum + i }));
+    ///     built = Some((total.clone(), t.output(total)));
+    /// });
+    /// let (total, total_rx) = built.unwrap();
+    ///
+    /// tx.send(1).unwrap();
+    /// tx.send(2).unwrap();
+    /// assert_eq!(total_rx.iter().take(3).last(), Some(3));
+    ///
+    /// // The fold's state carries on into the new subgraph
+    /// let mut out = None;
+    /// let attachment = handle.attach(&total, |t, total| {
+    ///     out = Some(t.output(total.lift(|sum| { sum * 10 })));
+    /// }).unwrap();
+    /// let out_rx = out.unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 30);
+    ///
+    /// tx.send(3).unwrap();
+    /// assert_eq!(out_rx.recv().unwrap(), 60);
+    ///
+    /// // Detached subgraphs exit, closing their outputs
+    /// handle.detach(attachment);
+    /// assert!(out_rx.recv().is_err());
+    ///
+    /// tx.send(4).unwrap();
+    /// assert_eq!(total_rx.recv().unwrap(), 6);
+    /// assert_eq!(total_rx.recv().unwrap(), 10);
+    /// ```
+    ///
+    pub fn attach<A, F>(&mut self, branch: &Branch<A>, f: F) -> Option<Attachment> where
+        A: 'static + Clone + Send,
+        F: FnOnce(&Builder, Branch<A>),
+    {
+        if self.stopped.load(Ordering::SeqCst) {
+            return None
+        }
+
+        let builder = Builder::sharing(self.config.clone());
+        let node = Node::shared("attach", None, false, vec![branch.describe()]);
+
+        let (initial, ends) = branch.tap(&self.config, "attach");
+        let (exit, root) = match ends {
+            Some((tx, rx)) => {
+                let branch = branch.clone();
+                let exit = Box::new(move || branch.untap(&tx)) as Box<FnMut() + Send>;
+                (exit, builder.add(Channel::new(self.config.clone(), rx, initial, node)))
+            },
+            None => {
+                let exit = Box::new(|| {}) as Box<FnMut() + Send>;
+                (exit, builder.add(builder.value(initial)))
+            },
+        };
+
+        f(&builder, root);
+
+        if !builder.inputs.borrow().is_empty() {
+            panic!("attached subgraphs can't create inputs");
+        }
+
+        let runners = fuse(builder.runners.into_inner());
+        let mut nodes = Vec::new();
+
+        match self.config.executor {
+            Executor::ThreadPerNode => {
+                for (idx, runner) in runners.into_iter().enumerate() {
+                    let name = runner.name().unwrap_or_else(|| format!("attached runner {}", idx));
+                    let thread_name = name.clone();
+                    let supervisor = self.config.supervisor().clone();
+                    let handle = spawn(name.clone(), move || {
+                        supervise(&supervisor, thread_name, move || runner.run());
+                    });
+
+                    nodes.push((name, handle));
+                }
+            },
+            _ => {
+                // Runners connect their nodes to the topology's scheduler,
+                // whose workers run them from then on
+                for (idx, runner) in runners.into_iter().enumerate() {
+                    supervise(self.config.supervisor(), format!("attached runner {}", idx), move || runner.run());
+                }
+            },
+        }
+
+        debug!("----> TOPOLOGY ATTACHED {} RUNNERS", nodes.len());
+        Some(Attachment {
+            exit,
+            nodes,
+        })
+    }
+
+    /// Stop a subgraph attached with `attach` and wait for it to exit
+    ///
+    /// `Event::Exit` is sent to the subgraph between two of its fork's
+    /// events, so its outputs are closed after the same event whichever
+    /// output is read.
+    ///
+    pub fn detach(&mut self, attachment: Attachment) {
+        let Attachment { mut exit, nodes } = attachment;
+        exit();
+
+        for (_, handle) in nodes.into_iter() {
+            let _ = handle.join();
+        }
+        debug!("----> TOPOLOGY DETACHED");
+    }
+
+    /// Set what happens to the topology when this handle is dropped
+    ///
+    pub fn set_drop_policy(&mut self, policy: DropPolicy) {
+        self.drop_policy = policy;
+    }
+}
+
+impl Drop for TopologyHandle {
+    fn drop(&mut self) {
+        match self.drop_policy {
+            DropPolicy::Detach => {},
+            DropPolicy::Stop => self.stop(),
+            DropPolicy::StopAndJoin(timeout) => {
+                let _ = self.stop_and_join(timeout);
+            },
         }
-        debug!("----> TOPOLOGY DROPPED");
     }
 }